@@ -18,7 +18,7 @@ pub struct BoxStats {
     pub outliers: Vec<f64>,
 }
 
-fn percentile_sorted(sorted: &[f64], p: f64) -> f64 {
+pub fn percentile_sorted(sorted: &[f64], p: f64) -> f64 {
     let n = sorted.len();
     if n == 0 { return f64::NAN; }
     let r = p * (n as f64 - 1.0);
@@ -39,13 +39,12 @@ pub fn calculate_freq_table_logic(data: &[f64], nbins: usize, minv: f64, maxv: f
     let mut cum_a = 0u32;
     let mut cum_r = 0.0f64;
     
-    for i in 0..nbins {
+    for (i, &abs_f) in counts.iter().enumerate() {
         let lower = minv + (i as f64) * amplitude;
         // Para asegurar que el límite superior del último coincida con maxv visualmente (opcional)
         let upper = lower + amplitude; 
         
         let midpoint = 0.5 * (lower + upper);
-        let abs_f = counts[i];
         cum_a += abs_f;
         let rel = (abs_f as f64) / n;
         cum_r += rel;
@@ -1,12 +1,14 @@
 use serde_json::json;
 use crate::json_helpers::to_cstring;
 use crate::errors::Error;
+use crate::probabilities::distribution::{Dist, Distribution};
+use crate::probabilities::fitting;
 use std::slice;
-use std::f64::consts::PI;
 
 // Imports
 use crate::stats::summary::{self, SummaryStats};
 use crate::aggregation::{histogram, boxplot, stem_leaf};
+use crate::utils::bin_index;
 
 // Estructuras internas para organizar mejor
 struct FitResult {
@@ -20,6 +22,7 @@ struct CurvesResult {
     x_plot: Vec<f64>,
     best_freq: Vec<f64>,
     expected_counts: Vec<f64>,
+    expected_by_value: Option<Vec<serde_json::Value>>, // Solo datos de conteo
 }
 
 // --- FUNCIÓN PRINCIPAL ---
@@ -46,7 +49,7 @@ pub fn analyze_distribution_json(
             forced_k
         } else {
             let s_k = crate::utils::sturges_bins(data.len());
-            if h_round && s_k % 2 == 0 { s_k + 1 } else { s_k }
+            if h_round && s_k.is_multiple_of(2) { s_k + 1 } else { s_k }
         };
 
         // 3. Estadísticas Descriptivas Base
@@ -105,7 +108,11 @@ pub fn analyze_distribution_json(
         let freq_table_json = json!({ "classes": classes, "amplitude": w });
 
         // 6. Best Fit & Curvas (Modularizado)
-        let fit = calculate_best_fit(&data, &summary_stats);
+        // Conteos (enteros >= 0) => familias discretas comparadas por su PMF. Con límites
+        // forzados los datos son marcas de clase y se tratan siempre como continuos.
+        let count_data = forced_min.is_nan() && forced_max.is_nan() && fitting::is_count_data(&data);
+        let fits = if count_data { calculate_discrete_fits(&data) } else { calculate_fits(&data, &summary_stats) };
+        let fit = &fits[0];
         
         // Usamos los bordes del histograma corregido para los conteos esperados
         let edges = if !hist_data.edges.is_empty() { 
@@ -114,7 +121,11 @@ pub fn analyze_distribution_json(
             (0..=k).map(|i| minv + i as f64 * w).collect() 
         };
         
-        let curves = calculate_curves(&fit, &edges, minv, maxv, n_f64, w);
+        let curves = if count_data {
+            calculate_discrete_curves(fit, &data, &edges, n_f64)
+        } else {
+            calculate_curves(fit, &edges, minv, maxv, n_f64, w)
+        };
 
         // 7. Salida JSON
         let out = json!({
@@ -128,8 +139,17 @@ pub fn analyze_distribution_json(
                 "aic": fit.aic,
                 "ll": fit.ll,
                 "params": fit.params,
-                "expected_counts": curves.expected_counts
+                "expected_counts": curves.expected_counts,
+                "expected_by_value": curves.expected_by_value
             },
+            "count_data": count_data,
+            // Ranking completo por AIC (solo candidatos aplicables a los datos)
+            "fits": fits.iter().filter(|f| f.ll.is_finite()).map(|f| json!({
+                "name": f.name,
+                "aic": f.aic,
+                "ll": f.ll,
+                "params": f.params
+            })).collect::<Vec<_>>(),
             "curves": {
                 "x": curves.x_plot,
                 "best_freq": curves.best_freq
@@ -141,82 +161,90 @@ pub fn analyze_distribution_json(
 }
 
 // --- LOGICA DE BEST FIT (Separada para claridad) ---
-fn calculate_best_fit(data: &[f64], stats: &SummaryStats) -> FitResult {
+// Candidato con los parámetros en el formato de Dist::from_fit; AIC = 2k - 2 ln L.
+// Parámetros vacíos o inválidos => ln L = -inf y AIC = inf (el candidato nunca gana).
+fn candidate(name: &'static str, params: Vec<f64>, data: &[f64]) -> FitResult {
+    let ll = Dist::from_fit(name, &params).map_or(f64::NEG_INFINITY, |d| d.ln_likelihood(data));
+    FitResult { name, aic: 2.0 * params.len() as f64 - 2.0 * ll, ll, params }
+}
+
+// Devuelve todos los candidatos ordenados por AIC; el primero es el mejor ajuste.
+// `data` debe venir ordenado (lo requiere el ajuste triangular).
+fn calculate_fits(data: &[f64], stats: &SummaryStats) -> Vec<FitResult> {
     let mean = stats.mean;
     let std = stats.std_pop;
     let minv = stats.min;
     let maxv = stats.max;
     let n = data.len() as f64;
 
+    let fit = |name: &'static str, params: Vec<f64>| candidate(name, params, data);
+
     // 1. Normal
-    let ll_norm = ll_normal(data, mean, std);
-    let aic_norm = 4.0 - 2.0 * ll_norm;
+    let normal = fit("normal", vec![mean, std]);
 
     // 2. Uniforme
     // IMPORTANTE: Al usar el minv/maxv forzados (el rango completo de la tabla),
     // la probabilidad uniforme es 1/(max-min). Si el rango es amplio, esta prob baja,
     // penalizando a la uniforme y permitiendo que la Normal gane si los datos se concentran al centro.
-    let ll_unif = ll_uniform(data, minv, maxv);
-    let aic_unif = 4.0 - 2.0 * ll_unif;
+    let uniform = fit("uniform", vec![minv, maxv]);
 
     // 3. Exponencial / LogNormal (Solo positivos)
     let has_negatives = minv < 0.0;
-    
-    let (aic_exp, ll_exp, exp_params) = if has_negatives || mean <= 0.0 {
-        (f64::INFINITY, f64::NEG_INFINITY, vec![])
+
+    let exponential = if has_negatives || mean <= 0.0 {
+        fit("exponential", vec![])
     } else {
-        let beta = mean;
-        let ll = ll_exponential(data, beta);
-        (2.0 - 2.0 * ll, ll, vec![beta])
+        fit("exponential", vec![mean])
     };
 
-    let (aic_logn, ll_logn, logn_params) = if has_negatives || minv <= 0.0 {
-         (f64::INFINITY, f64::NEG_INFINITY, vec![])
+    let lognormal = if has_negatives || minv <= 0.0 {
+        fit("lognormal", vec![])
     } else {
-         let ln_data: Vec<f64> = data.iter().map(|x| x.ln()).collect();
-         let ln_mean: f64 = ln_data.iter().sum::<f64>() / n;
-         let ln_var: f64 = ln_data.iter().map(|x| (x - ln_mean).powi(2)).sum::<f64>() / n;
-         let ln_sigma = ln_var.sqrt();
-         let ll = ll_lognormal(data, ln_mean, ln_sigma);
-         (4.0 - 2.0 * ll, ll, vec![ln_mean, ln_sigma])
+        let ln_data: Vec<f64> = data.iter().map(|x| x.ln()).collect();
+        let ln_mean: f64 = ln_data.iter().sum::<f64>() / n;
+        let ln_var: f64 = ln_data.iter().map(|x| (x - ln_mean).powi(2)).sum::<f64>() / n;
+        fit("lognormal", vec![ln_mean, ln_var.sqrt()])
     };
 
+    // 4. Máxima verosimilitud numérica (ver probabilities::fitting)
     let mut fits = vec![
-        FitResult { name: "normal", aic: aic_norm, ll: ll_norm, params: vec![mean, std] },
-        FitResult { name: "exponential", aic: aic_exp, ll: ll_exp, params: exp_params },
-        FitResult { name: "lognormal", aic: aic_logn, ll: ll_logn, params: logn_params },
-        FitResult { name: "uniform", aic: aic_unif, ll: ll_unif, params: vec![minv, maxv] },
+        normal,
+        exponential,
+        lognormal,
+        uniform,
+        fit("gamma", fitting::gamma(data).unwrap_or_default()),
+        fit("erlang", fitting::erlang(data).unwrap_or_default()),
+        fit("weibull", fitting::weibull(data).unwrap_or_default()),
+        fit("beta", fitting::scaled_beta(data, minv, maxv).unwrap_or_default()),
+        fit("logistic", fitting::logistic(data).unwrap_or_default()),
+        fit("gumbel", fitting::gumbel(data).unwrap_or_default()),
+        fit("pareto", fitting::pareto(data).unwrap_or_default()),
+        fit("triangular", fitting::triangular(data, minv, maxv).unwrap_or_default()),
     ];
 
     // Ordenar por mejor AIC (menor es mejor)
     fits.sort_by(|a, b| a.aic.partial_cmp(&b.aic).unwrap_or(std::cmp::Ordering::Equal));
-    fits.remove(0)
+    fits
+}
+
+// Datos de conteo: ln L es la suma de ln P(X = x_i), así que el AIC compara PMFs. Con datos
+// constantes la binomial (degenerada, p = 1) y la binomial negativa no aplican.
+fn calculate_discrete_fits(data: &[f64]) -> Vec<FitResult> {
+    let mut fits = vec![
+        candidate("poisson", fitting::poisson(data).unwrap_or_default(), data),
+        candidate("binomial", fitting::binomial(data).unwrap_or_default(), data),
+        candidate("geometric", fitting::geometric(data).unwrap_or_default(), data),
+        candidate("negative_binomial", fitting::negative_binomial(data).unwrap_or_default(), data),
+    ];
+    fits.sort_by(|a, b| a.aic.partial_cmp(&b.aic).unwrap_or(std::cmp::Ordering::Equal));
+    fits
 }
 
 fn calculate_curves(fit: &FitResult, edges: &[f64], minv: f64, maxv: f64, n_f64: f64, w: f64) -> CurvesResult {
-    let params = &fit.params;
-    let name = fit.name;
-
-    // Closures para seleccionar la función matemática correcta
-    let cdf = |x: f64| -> f64 {
-        match name {
-            "normal" => normal_cdf(x, params[0], params[1]),
-            "exponential" => if !params.is_empty() { exponential_cdf(x, params[0]) } else { 0.0 },
-            "lognormal" => if !params.is_empty() { lognormal_cdf(x, params[0], params[1]) } else { 0.0 },
-            "uniform" => uniform_cdf(x, params[0], params[1]),
-            _ => 0.0,
-        }
-    };
-    
-    let pdf = |x: f64| -> f64 {
-        match name {
-            "normal" => normal_pdf(x, params[0], params[1]),
-            "exponential" => if !params.is_empty() { exponential_pdf(x, params[0]) } else { 0.0 },
-            "lognormal" => if !params.is_empty() { lognormal_pdf(x, params[0], params[1]) } else { 0.0 },
-            "uniform" => uniform_pdf(x, params[0], params[1]),
-            _ => 0.0,
-        }
-    };
+    // Sin un ajuste válido las curvas quedan en 0
+    let dist = Dist::from_fit(fit.name, &fit.params).ok();
+    let cdf = |x: f64| dist.as_ref().map_or(0.0, |d| d.cdf(x));
+    let pdf = |x: f64| dist.as_ref().map_or(0.0, |d| d.pdf(x));
 
     // 1. Expected Counts (Chi-cuadrado visual)
     let mut expected_counts = Vec::with_capacity(edges.len().saturating_sub(1));
@@ -240,94 +268,113 @@ fn calculate_curves(fit: &FitResult, edges: &[f64], minv: f64, maxv: f64, n_f64:
         best_freq.push(y);
     }
 
-    CurvesResult { x_plot, best_freq, expected_counts }
+    CurvesResult { x_plot, best_freq, expected_counts, expected_by_value: None }
 }
 
-// --- MATH HELPERS (Mantener igual que antes) ---
-// (Copia aquí las funciones ll_normal, ll_exponential, etc. del código previo)
-// (Son idénticas a las que ya tenías)
-fn ll_normal(data: &[f64], mu: f64, sigma: f64) -> f64 {
-    if sigma <= 0.0 { return f64::NEG_INFINITY; }
-    let n = data.len() as f64;
-    let term1 = -0.5 * n * (2.0 * PI).ln();
-    let term2 = -n * sigma.ln();
-    let sum_sq: f64 = data.iter().map(|x| (x - mu).powi(2)).sum();
-    term1 + term2 - sum_sq / (2.0 * sigma * sigma)
-}
-fn ll_exponential(data: &[f64], beta: f64) -> f64 {
-    if beta <= 0.0 { return f64::NEG_INFINITY; }
-    let n = data.len() as f64;
-    let sum: f64 = data.iter().sum();
-    -n * beta.ln() - sum / beta
-}
-fn ll_lognormal(data: &[f64], mu: f64, sigma: f64) -> f64 {
-    if sigma <= 0.0 { return f64::NEG_INFINITY; }
-    let n = data.len() as f64;
-    let term1 = -0.5 * n * (2.0 * PI).ln();
-    let term2 = -n * sigma.ln();
-    let mut sum_log_x = 0.0;
-    let mut sum_sq = 0.0;
-    for &x in data {
-        if x <= 0.0 { return f64::NEG_INFINITY; }
-        let lx = x.ln();
-        sum_log_x += lx;
-        sum_sq += (lx - mu).powi(2);
+// Conteos: frecuencia esperada n · P(X = v) para cada entero del rango observado; por clase
+// se suma la de los enteros que el histograma asigna a esa clase.
+fn calculate_discrete_curves(fit: &FitResult, data: &[f64], edges: &[f64], n_f64: f64) -> CurvesResult {
+    let dist = Dist::from_fit(fit.name, &fit.params).ok();
+    let pmf = |x: f64| dist.as_ref().map_or(0.0, |d| d.pdf(x));
+
+    let nbins = edges.len().saturating_sub(1);
+    let minv = data[0];
+    let maxv = data[data.len() - 1];
+    let width = if nbins > 0 { edges[1] - edges[0] } else { 1.0 };
+
+    let table = fitting::value_counts(data);
+    let mut observed = table.iter().peekable();
+    let mut expected_counts = vec![0.0; nbins];
+    let mut expected_by_value = Vec::new();
+    let mut x_plot = Vec::new();
+    let mut best_freq = Vec::new();
+
+    let mut v = minv;
+    while v <= maxv {
+        let expected = pmf(v) * n_f64;
+        let obs = match observed.peek() {
+            Some(&&(value, count)) if value == v => { observed.next(); count }
+            _ => 0.0,
+        };
+        if nbins > 0 { expected_counts[bin_index(v, minv, width, nbins)] += expected; }
+        expected_by_value.push(json!({ "value": v, "observed": obs, "expected": expected }));
+        x_plot.push(v);
+        best_freq.push(expected * width); // Misma escala que la curva continua (PDF · n · w)
+        v += 1.0;
     }
-    term1 + term2 - sum_log_x - sum_sq / (2.0 * sigma * sigma)
-}
-fn ll_uniform(data: &[f64], a: f64, b: f64) -> f64 {
-    if a >= b { return f64::NEG_INFINITY; }
-    let n = data.len() as f64;
-    // IMPORTANTE: En datos reconstruidos, es posible que un punto flotante se salga minimamente
-    // del rango si no tenemos cuidado, pero aquí a y b son los forzados.
-    // Relajamos un poco la validación para float errors o asumimos consistencia.
-    for &x in data { if x < a || x > b { return f64::NEG_INFINITY; } }
-    -n * (b - a).ln()
-}
 
-// PDF Functions
-fn normal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
-    if sigma <= 0.0 { return 0.0; }
-    let z = (x - mu) / sigma;
-    (1.0 / (sigma * (2.0 * PI).sqrt())) * (-0.5 * z * z).exp()
+    CurvesResult { x_plot, best_freq, expected_counts, expected_by_value: Some(expected_by_value) }
 }
-fn exponential_pdf(x: f64, beta: f64) -> f64 {
-    if x < 0.0 || beta <= 0.0 { 0.0 } else { (1.0 / beta) * (-x / beta).exp() }
-}
-fn lognormal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
-    if x <= 0.0 || sigma <= 0.0 { 0.0 } else {
-        (1.0 / (x * sigma * (2.0 * PI).sqrt())) * (-0.5 * ((x.ln() - mu) / sigma).powi(2)).exp()
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json_helpers::free_c_string;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use rand_distr::Distribution as _;
+    use serde_json::Value;
+    use std::ffi::CStr;
+
+    fn analyze(data: &[f64], forced_min: f64, forced_max: f64) -> Value {
+        let ptr = analyze_distribution_json(data.as_ptr(), data.len(), false, 0, forced_min, forced_max).unwrap();
+        let out = unsafe { serde_json::from_str(CStr::from_ptr(ptr).to_str().unwrap()).unwrap() };
+        free_c_string(ptr);
+        out
     }
-}
-fn uniform_pdf(x: f64, a: f64, b: f64) -> f64 {
-    if x >= a && x <= b && b > a { 1.0 / (b - a) } else { 0.0 }
-}
 
-// CDF Functions
-fn normal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
-    if sigma <= 0.0 { return if x >= mu { 1.0 } else { 0.0 }; }
-    let z = (x - mu) / (sigma * 2.0_f64.sqrt());
-    0.5 * (1.0 + erf_approx(z))
-}
-fn erf_approx(x: f64) -> f64 {
-    let a1 =  0.254829592;
-    let a2 = -0.284496736;
-    let a3 =  1.421413741;
-    let a4 = -1.453152027;
-    let a5 =  1.061405429;
-    let p  =  0.3275911;
-    let sign = if x < 0.0 { -1.0 } else { 1.0 };
-    let x_abs = x.abs();
-    let t = 1.0 / (1.0 + p * x_abs);
-    let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-x_abs * x_abs).exp();
-    sign * y
-}
-fn exponential_cdf(x: f64, beta: f64) -> f64 {
-    if x < 0.0 { 0.0 } else { 1.0 - (-x / beta).exp() }
-}
-fn lognormal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
-    if x <= 0.0 { 0.0 } else { normal_cdf(x.ln(), mu, sigma) }
+    fn sample(dist: Dist, n: usize) -> Vec<f64> {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let sampler = dist.sampler();
+        (0..n).map(|_| sampler.sample(&mut rng)).collect()
+    }
+
+    #[test]
+    fn counts_are_fitted_with_discrete_families() {
+        let data = sample(Dist::Poisson { lambda: 4.0 }, 2_000);
+        let out = analyze(&data, f64::NAN, f64::NAN);
+        assert_eq!(out["count_data"], true);
+        assert_eq!(out["best_fit"]["name"], "poisson");
+        let names: Vec<&str> = out["fits"].as_array().unwrap().iter().map(|f| f["name"].as_str().unwrap()).collect();
+        assert!(names.iter().all(|n| ["poisson", "binomial", "geometric", "negative_binomial"].contains(n)), "{names:?}");
+
+        // Un entero por fila, del mínimo al máximo observado, con las frecuencias observadas
+        let rows = out["best_fit"]["expected_by_value"].as_array().unwrap();
+        let (lo, hi) = data.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        assert_eq!(rows.len(), (hi - lo) as usize + 1);
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(row["value"].as_f64().unwrap(), lo + i as f64);
+        }
+        let observed: f64 = rows.iter().map(|r| r["observed"].as_f64().unwrap()).sum();
+        let expected: f64 = rows.iter().map(|r| r["expected"].as_f64().unwrap()).sum();
+        assert_eq!(observed, data.len() as f64);
+        assert!(expected > 0.99 * observed && expected <= observed, "{expected}");
+        let by_class: f64 = out["best_fit"]["expected_counts"].as_array().unwrap().iter().map(|c| c.as_f64().unwrap()).sum();
+        assert!((by_class - expected).abs() < 1e-6 * expected);
+    }
+
+    #[test]
+    fn non_counts_and_forced_bounds_are_continuous() {
+        let data = sample(Dist::Gamma { shape: 2.0, scale: 1.5 }, 500);
+        let out = analyze(&data, f64::NAN, f64::NAN);
+        assert_eq!(out["count_data"], false);
+        assert!(out["best_fit"]["expected_by_value"].is_null());
+
+        // Marcas de clase enteras con límites de la tabla: siempre continuas
+        let marks = [5.0, 5.0, 15.0, 15.0, 15.0, 25.0];
+        let out = analyze(&marks, 0.0, 30.0);
+        assert_eq!(out["count_data"], false);
+        assert!(out["best_fit"]["expected_by_value"].is_null());
+    }
+
+    #[test]
+    fn constant_counts_do_not_pick_a_degenerate_binomial() {
+        let out = analyze(&[3.0; 100], f64::NAN, f64::NAN);
+        assert_eq!(out["count_data"], true);
+        assert_eq!(out["best_fit"]["name"], "poisson");
+        let ll = out["best_fit"]["ll"].as_f64().unwrap();
+        assert!(ll.is_finite() && ll < 0.0, "{ll}");
+        let names: Vec<&str> = out["fits"].as_array().unwrap().iter().map(|f| f["name"].as_str().unwrap()).collect();
+        assert!(!names.contains(&"binomial") && !names.contains(&"negative_binomial"), "{names:?}");
+    }
 }
-fn uniform_cdf(x: f64, a: f64, b: f64) -> f64 {
-    if x < a { 0.0 } else if x >= b { 1.0 } else { (x - a) / (b - a) }
-}
\ No newline at end of file
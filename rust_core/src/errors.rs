@@ -2,6 +2,7 @@
 use std::fmt; // <--- Importante
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     NullOrEmptyInput,
     SerdeError(String),
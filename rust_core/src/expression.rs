@@ -0,0 +1,331 @@
+// src/expression.rs
+// Parser de expresiones matemáticas sencillas (descenso recursivo) para integrandos definidos
+// por el usuario: f(x), f(x, y), f(x1, ..., xn).
+use crate::errors::Error;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Num(f64),
+    Var(usize),
+    Neg(Box<Node>),
+    Bin(char, Box<Node>, Box<Node>),
+    Call(Func, Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Func {
+    Sin, Cos, Tan, Asin, Acos, Atan, Sinh, Cosh, Tanh,
+    Exp, Ln, Log10, Sqrt, Abs, Floor, Ceil,
+    Pow, Min, Max,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<(Func, usize)> {
+        let f = match name {
+            "sin" => (Func::Sin, 1),
+            "cos" => (Func::Cos, 1),
+            "tan" => (Func::Tan, 1),
+            "asin" => (Func::Asin, 1),
+            "acos" => (Func::Acos, 1),
+            "atan" => (Func::Atan, 1),
+            "sinh" => (Func::Sinh, 1),
+            "cosh" => (Func::Cosh, 1),
+            "tanh" => (Func::Tanh, 1),
+            "exp" => (Func::Exp, 1),
+            "ln" => (Func::Ln, 1),
+            "log" | "log10" => (Func::Log10, 1),
+            "sqrt" => (Func::Sqrt, 1),
+            "abs" => (Func::Abs, 1),
+            "floor" => (Func::Floor, 1),
+            "ceil" => (Func::Ceil, 1),
+            "pow" => (Func::Pow, 2),
+            "min" => (Func::Min, 2),
+            "max" => (Func::Max, 2),
+            _ => return None,
+        };
+        Some(f)
+    }
+
+    fn apply(self, a: &[f64]) -> f64 {
+        match self {
+            Func::Sin => a[0].sin(),
+            Func::Cos => a[0].cos(),
+            Func::Tan => a[0].tan(),
+            Func::Asin => a[0].asin(),
+            Func::Acos => a[0].acos(),
+            Func::Atan => a[0].atan(),
+            Func::Sinh => a[0].sinh(),
+            Func::Cosh => a[0].cosh(),
+            Func::Tanh => a[0].tanh(),
+            Func::Exp => a[0].exp(),
+            Func::Ln => a[0].ln(),
+            Func::Log10 => a[0].log10(),
+            Func::Sqrt => a[0].sqrt(),
+            Func::Abs => a[0].abs(),
+            Func::Floor => a[0].floor(),
+            Func::Ceil => a[0].ceil(),
+            Func::Pow => a[0].powf(a[1]),
+            Func::Min => a[0].min(a[1]),
+            Func::Max => a[0].max(a[1]),
+        }
+    }
+}
+
+/// Expresión compilada. Variables: x, y, z (índices 0, 1, 2) o x1..xn (índices 0..n-1).
+#[derive(Debug, Clone)]
+pub struct Expression {
+    root: Node,
+    n_vars: usize, // Mayor índice de variable usado + 1
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut parser = Parser { chars: source.chars().collect(), pos: 0, n_vars: 0 };
+        let root = parser.expr()?;
+        parser.skip_ws();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("Símbolo inesperado"));
+        }
+        Ok(Expression { root, n_vars: parser.n_vars })
+    }
+
+    /// Número de variables que la expresión necesita.
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    pub fn eval(&self, vars: &[f64]) -> f64 {
+        eval(&self.root, vars)
+    }
+}
+
+fn eval(node: &Node, vars: &[f64]) -> f64 {
+    match node {
+        Node::Num(v) => *v,
+        Node::Var(i) => vars[*i],
+        Node::Neg(a) => -eval(a, vars),
+        Node::Bin(op, a, b) => {
+            let (a, b) = (eval(a, vars), eval(b, vars));
+            match op {
+                '+' => a + b,
+                '-' => a - b,
+                '*' => a * b,
+                '/' => a / b,
+                _ => a.powf(b),
+            }
+        },
+        Node::Call(f, args) => {
+            let values: Vec<f64> = args.iter().map(|a| eval(a, vars)).collect();
+            f.apply(&values)
+        },
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    n_vars: usize,
+}
+
+impl Parser {
+    fn error(&self, msg: &str) -> Error {
+        Error::Other(format!("{} en la posición {} de la expresión", msg, self.pos + 1))
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Node, Error> {
+        let mut node = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            node = Node::Bin(op, Box::new(node), Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    // term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Node, Error> {
+        let mut node = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            node = Node::Bin(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    // unary := ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.eat('-') {
+            return Ok(Node::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    // power := atom ('^' unary)?   (asociativa por la derecha: 2^3^2 = 2^9)
+    fn power(&mut self) -> Result<Node, Error> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            return Ok(Node::Bin('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let node = self.expr()?;
+                if !self.eat(')') { return Err(self.error("Falta ')'")); }
+                Ok(node)
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => self.identifier(),
+            Some(_) => Err(self.error("Símbolo inesperado")),
+            None => Err(self.error("Expresión incompleta")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Node, Error> {
+        let start = self.pos;
+        while self.pos < self.chars.len() && (self.chars[self.pos].is_ascii_digit() || self.chars[self.pos] == '.') {
+            self.pos += 1;
+        }
+        // Notación científica: 1e-3, 2.5E4
+        if self.pos < self.chars.len() && matches!(self.chars[self.pos], 'e' | 'E') {
+            let save = self.pos;
+            self.pos += 1;
+            if self.pos < self.chars.len() && matches!(self.chars[self.pos], '+' | '-') { self.pos += 1; }
+            if self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() {
+                while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() { self.pos += 1; }
+            } else {
+                self.pos = save;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map(Node::Num).map_err(|_| {
+            self.pos = start;
+            self.error(&format!("Número inválido '{}'", text))
+        })
+    }
+
+    fn identifier(&mut self) -> Result<Node, Error> {
+        let start = self.pos;
+        while self.pos < self.chars.len() && (self.chars[self.pos].is_ascii_alphanumeric() || self.chars[self.pos] == '_') {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect::<String>().to_lowercase();
+
+        if let Some((func, arity)) = Func::from_name(&name) {
+            if !self.eat('(') { return Err(self.error(&format!("Se esperaba '(' después de {}", name))); }
+            let mut args = vec![self.expr()?];
+            while self.eat(',') {
+                args.push(self.expr()?);
+            }
+            if !self.eat(')') { return Err(self.error("Falta ')'")); }
+            if args.len() != arity {
+                return Err(self.error(&format!("{} recibe {} argumento(s)", name, arity)));
+            }
+            return Ok(Node::Call(func, args));
+        }
+
+        let var = match name.as_str() {
+            "pi" => return Ok(Node::Num(std::f64::consts::PI)),
+            "e" => return Ok(Node::Num(std::f64::consts::E)),
+            "x" => 0,
+            "y" => 1,
+            "z" => 2,
+            _ => match name.strip_prefix('x').and_then(|i| i.parse::<usize>().ok()) {
+                Some(i) if i >= 1 => i - 1,
+                _ => {
+                    self.pos = start;
+                    return Err(self.error(&format!("Identificador desconocido '{}'", name)));
+                },
+            },
+        };
+        self.n_vars = self.n_vars.max(var + 1);
+        Ok(Node::Var(var))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(source: &str) -> f64 {
+        Expression::parse(source).unwrap().eval(&[])
+    }
+
+    fn parse_error(source: &str) -> String {
+        match Expression::parse(source) {
+            Err(Error::Other(msg)) => msg,
+            _ => panic!("'{}' debería fallar", source),
+        }
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        // El menos unario liga más débil que la potencia; '^' asocia por la derecha
+        assert_eq!(value("-2^2"), -4.0);
+        assert_eq!(value("(-2)^2"), 4.0);
+        assert_eq!(value("2^3^2"), 512.0);
+        assert_eq!(value("2^-1"), 0.5);
+        assert_eq!(value("1 + 2 * 3 - 8 / 4"), 5.0);
+        assert_eq!(value("2 * -3"), -6.0);
+    }
+
+    #[test]
+    fn numbers_constants_and_functions() {
+        assert_eq!(value("1e-3"), 0.001);
+        assert_eq!(value("2.5E4"), 25_000.0);
+        assert_eq!(value(".5"), 0.5);
+        assert_eq!(value("pow(2, 10) + max(1, 3) - min(1, 3)"), 1026.0);
+        assert!((value("sin(pi / 2) + ln(e)") - 2.0).abs() < 1e-15);
+        // "e" suelta es la constante de Euler, no un exponente
+        assert!((value("2*e") - 2.0 * std::f64::consts::E).abs() < 1e-15);
+    }
+
+    #[test]
+    fn variables() {
+        let f = Expression::parse("x^2 + y*z").unwrap();
+        assert_eq!(f.n_vars(), 3);
+        assert_eq!(f.eval(&[3.0, 2.0, 5.0]), 19.0);
+        let g = Expression::parse("X1 + x4").unwrap();
+        assert_eq!(g.n_vars(), 4);
+        assert_eq!(g.eval(&[1.0, 0.0, 0.0, 2.0]), 3.0);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(parse_error("sin(1, 2)").contains("sin recibe 1"));
+        assert!(parse_error("pow(2)").contains("pow recibe 2"));
+        assert!(parse_error("foo + 1").contains("Identificador desconocido 'foo'"));
+        assert!(parse_error("x0").contains("Identificador desconocido"));
+        assert!(parse_error("sqrt 4").contains("Se esperaba '('"));
+        assert!(parse_error("(1 + 2").contains("Falta ')'"));
+        assert!(parse_error("1 +").contains("Expresión incompleta"));
+        assert!(parse_error("1 2").contains("posición 3"));
+        assert!(parse_error("1..2").contains("Número inválido"));
+    }
+}
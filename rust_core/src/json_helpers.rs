@@ -1,7 +1,8 @@
 // src/json_helpers.rs
-use serde::Serialize;
-use std::ffi::{CString};
+use serde::{de::DeserializeOwned, Serialize};
+use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use crate::errors::Error;
 
 /// Convert serializable obj -> *mut c_char (CString::into_raw)
 /// Caller must call free_c_string.
@@ -12,9 +13,32 @@ pub fn to_cstring<T: Serialize>(obj: &T) -> *mut c_char {
 }
 
 
-/// Helper para convertir un puntero *mut c_char de vuelta a String de Rust.
-/// Útil cuando llamamos internamente a funciones que retornan CStrings.
-/// OJO: No libera el puntero original, eso debe hacerse con free_c_string después.
+/// JSON (C string) -> request -> función -> JSON. Los errores se devuelven como {"error": "..."}.
+pub fn run_json<Req: DeserializeOwned, Res: Serialize>(
+    json_request: *const c_char,
+    handler: impl FnOnce(&Req) -> Result<Res, Error>,
+) -> *mut c_char {
+    if json_request.is_null() {
+        return to_cstring(&serde_json::json!({"error": "Null input pointer"}));
+    }
+
+    let req_result: Result<Req, _> = unsafe {
+        let c_str = CStr::from_ptr(json_request);
+        serde_json::from_str(c_str.to_str().unwrap_or(""))
+    };
+
+    match req_result {
+        Ok(req) => match handler(&req) {
+            Ok(res) => to_cstring(&res),
+            Err(e) => to_cstring(&serde_json::json!({"error": e.to_string()})),
+        },
+        Err(e) => to_cstring(&serde_json::json!({"error": format!("JSON Error: {}", e)})),
+    }
+}
+
+// Helper para convertir un puntero *mut c_char de vuelta a String de Rust.
+// Útil cuando llamamos internamente a funciones que retornan CStrings.
+// OJO: No libera el puntero original, eso debe hacerse con free_c_string después.
 //pub unsafe fn ptr_to_string(ptr: *mut c_char) -> String {
 //    if ptr.is_null() { return String::from("{}"); }
 //    let c_str = CStr::from_ptr(ptr);
@@ -1,7 +1,11 @@
 // src/lib.rs
+// Las funciones FFI reciben punteros crudos de Dart por diseño.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 mod ffi;
 mod json_helpers;
 mod utils;
+mod expression;
 mod errors;
 
 mod analysis;
@@ -10,6 +14,7 @@ pub mod sampling;
 pub mod aggregation;
 pub mod stats;
 pub mod probabilities;
+pub mod markov;
 
 pub use crate::json_helpers::free_c_string;
 use std::ffi::{c_char};
@@ -18,6 +23,7 @@ use std::ffi::{c_char};
 pub mod simulations {
     pub mod carwash;
     pub mod montecarlo;
+    pub mod inventory;
 }
 
 // 1. ANALYZE (Función Principal Unificada)
@@ -73,6 +79,48 @@ pub extern "C" fn generate_binomial(ptr: *mut f64, len: usize, n: u64, p: f64, s
     sampling::generator::generate_binomial(ptr, len, n, p, seed);
 }
 
+// generate_variates(ptr, len, json) -> {"count", "seed"}; distribución descrita en JSON
+#[no_mangle]
+pub extern "C" fn generate_variates(ptr: *mut f64, len: usize, json_request: *const c_char) -> *mut c_char {
+    sampling::generator::generate_variates(ptr, len, json_request)
+}
+
+// generate_quasi(ptr, len, json) -> puntos Halton/Sobol/van der Corput por filas + discrepancia
+#[no_mangle]
+pub extern "C" fn generate_quasi(ptr: *mut f64, len: usize, json_request: *const c_char) -> *mut c_char {
+    sampling::quasi::generate_quasi(ptr, len, json_request)
+}
+
+// montecarlo_stats_json(json) -> distribución muestral de la media (TLC)
+#[no_mangle]
+pub extern "C" fn montecarlo_stats_json(json_request: *const c_char) -> *mut c_char {
+    sampling::generator::montecarlo_stats_json(json_request)
+}
+
+// stochastic_process_json(json) -> trayectorias de procesos estocásticos
+#[no_mangle]
+pub extern "C" fn stochastic_process_json(json_request: *const c_char) -> *mut c_char {
+    sampling::processes::stochastic_process_json(json_request)
+}
+
+// prng_json(json) -> generadores congruenciales / cuadrados medios con periodo
+#[no_mangle]
+pub extern "C" fn prng_json(json_request: *const c_char) -> *mut c_char {
+    sampling::prng::prng_json(json_request)
+}
+
+// randomness_tests_json(json) -> pruebas de aleatoriedad (chi-cuadrada, KS, rachas, póker, ...)
+#[no_mangle]
+pub extern "C" fn randomness_tests_json(json_request: *const c_char) -> *mut c_char {
+    sampling::randomness::randomness_tests_json(json_request)
+}
+
+// generation_method_json(json) -> variables por transformada inversa, aceptación-rechazo, convolución o composición
+#[no_mangle]
+pub extern "C" fn generation_method_json(json_request: *const c_char) -> *mut c_char {
+    sampling::methods::generation_method_json(json_request)
+}
+
 // sturges bins helper
 #[no_mangle]
 pub extern "C" fn sturges_bins(n: usize) -> usize {
@@ -147,6 +195,28 @@ pub extern "C" fn simulation_montecarlo(json_config: *const libc::c_char) -> *mu
     simulations::montecarlo::run_montecarlo(json_config)
 }
 
+// Integración Monte Carlo (acierto-fallo / media muestral) y demo de pi
+#[no_mangle]
+pub extern "C" fn montecarlo_integrate_json(json_request: *const c_char) -> *mut c_char {
+    simulations::montecarlo::integration::integrate_json(json_request)
+}
+
+#[no_mangle]
+pub extern "C" fn simulate_inventory(json_config: *const c_char) -> *mut c_char {
+    simulations::inventory::run_inventory(json_config)
+}
+
+// ---------- CADENAS DE MARKOV ----------
+#[no_mangle]
+pub extern "C" fn markov_chain_json(json_request: *const c_char) -> *mut c_char {
+    markov::run_dtmc(json_request)
+}
+
+#[no_mangle]
+pub extern "C" fn ctmc_json(json_request: *const c_char) -> *mut c_char {
+    markov::run_ctmc(json_request)
+}
+
 // ---------- CALCULADORA INVERSA (CDF) ----------
 #[no_mangle]
 pub extern "C" fn calculate_inverse_cdf(json_request: *const libc::c_char) -> *mut libc::c_char {
@@ -175,4 +245,10 @@ pub extern "C" fn calculate_inverse_cdf(json_request: *const libc::c_char) -> *m
         },
         Err(e) => to_cstring(&serde_json::json!({"error": format!("JSON Parse Error: {}", e)}))
     }
+}
+
+// ---------- CALCULADORA DE PROBABILIDADES ----------
+#[no_mangle]
+pub extern "C" fn probability_calculator_json(json_request: *const c_char) -> *mut c_char {
+    probabilities::calculator::calculate_json(json_request)
 }
\ No newline at end of file
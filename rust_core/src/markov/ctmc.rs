@@ -0,0 +1,334 @@
+// src/markov/ctmc.rs
+use serde::{Deserialize, Serialize};
+use rand::prelude::*;
+use rand::distributions::WeightedIndex;
+use rand_chacha::ChaCha20Rng;
+use rand_distr::Exp;
+use crate::errors::Error;
+use super::{dtmc, linalg};
+
+const TOL: f64 = 1e-9;
+
+// Proceso de nacimiento y muerte con estados 0..n-1:
+// birth_rates[i] = lambda_i (i -> i+1), death_rates[i] = mu_{i+1} (i+1 -> i)
+#[derive(Deserialize)]
+pub struct BirthDeath {
+    pub birth_rates: Vec<f64>,
+    pub death_rates: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct CtmcPathRequest {
+    pub start: usize,
+    pub max_time: f64,
+    #[serde(default)]
+    pub max_events: Option<usize>, // Por defecto 10 000
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct CtmcRequest {
+    #[serde(default)]
+    pub generator: Option<Vec<Vec<f64>>>, // Matriz Q (filas suman 0)
+    #[serde(default)]
+    pub birth_death: Option<BirthDeath>,  // Alternativa a `generator`
+    #[serde(default)]
+    pub initial: Option<Vec<f64>>,        // Por defecto, arranca en el estado 0
+    #[serde(default)]
+    pub times: Vec<f64>,                  // Instantes para p(t)
+    #[serde(default)]
+    pub simulate: Option<CtmcPathRequest>,
+}
+
+#[derive(Serialize)]
+pub struct TransientPoint {
+    pub t: f64,
+    pub probabilities: Vec<f64>,
+    pub poisson_terms: usize, // Términos usados en la uniformización
+}
+
+#[derive(Serialize)]
+pub struct CtmcPath {
+    pub times: Vec<f64>,       // Instantes de salto (inicia en 0)
+    pub states: Vec<usize>,    // Estado a partir de cada instante
+    pub end_time: f64,
+    pub time_in_state: Vec<f64>, // Fracción del tiempo en cada estado
+}
+
+#[derive(Serialize)]
+pub struct CtmcResponse {
+    pub n_states: usize,
+    pub generator: Vec<Vec<f64>>,
+    pub uniformization_rate: f64,
+    pub steady_state: Option<Vec<f64>>,
+    pub steady_state_unique: bool,
+    pub transient: Vec<TransientPoint>,
+    pub path: Option<CtmcPath>,
+}
+
+fn birth_death_generator(bd: &BirthDeath) -> Result<Vec<Vec<f64>>, Error> {
+    if bd.birth_rates.len() != bd.death_rates.len() {
+        return Err(Error::Other(format!(
+            "birth_rates ({}) y death_rates ({}) deben tener el mismo largo",
+            bd.birth_rates.len(), bd.death_rates.len()
+        )));
+    }
+    let n = bd.birth_rates.len() + 1;
+    let mut q = vec![vec![0.0; n]; n];
+    for i in 0..n - 1 {
+        q[i][i + 1] = bd.birth_rates[i];
+        q[i + 1][i] = bd.death_rates[i];
+    }
+    for (i, row) in q.iter_mut().enumerate() {
+        row[i] = -row.iter().sum::<f64>();
+    }
+    Ok(q)
+}
+
+/// Valida un generador: cuadrado, tasas fuera de la diagonal >= 0 y filas que suman 0.
+pub fn validate_generator(q: &[Vec<f64>]) -> Result<(), Error> {
+    let n = q.len();
+    if n == 0 { return Err(Error::NullOrEmptyInput); }
+    let mut problems = Vec::new();
+    for (i, row) in q.iter().enumerate() {
+        if row.len() != n {
+            problems.push(format!("fila {} tiene {} columnas (se esperaban {})", i, row.len(), n));
+            continue;
+        }
+        if let Some((j, x)) = row.iter().enumerate().find(|&(j, x)| !x.is_finite() || (j != i && *x < 0.0)) {
+            problems.push(format!("tasa inválida {} en ({}, {})", x, i, j));
+            continue;
+        }
+        let sum: f64 = row.iter().sum();
+        let scale = row.iter().map(|x| x.abs()).fold(1.0, f64::max);
+        if sum.abs() > 1e-9 * scale {
+            problems.push(format!("fila {} suma {} (debe sumar 0)", i, sum));
+        }
+    }
+    if problems.is_empty() { Ok(()) } else { Err(Error::Other(format!("Generador inválido: {}", problems.join("; ")))) }
+}
+
+pub fn analyze(req: &CtmcRequest) -> Result<CtmcResponse, Error> {
+    let q = match (&req.generator, &req.birth_death) {
+        (Some(g), None) => g.clone(),
+        (None, Some(bd)) => birth_death_generator(bd)?,
+        _ => return Err(Error::Other("Indique exactamente uno de 'generator' o 'birth_death'".into())),
+    };
+    validate_generator(&q)?;
+    let n = q.len();
+
+    let initial = match &req.initial {
+        Some(v) => {
+            let s: f64 = v.iter().sum();
+            if v.len() != n || v.iter().any(|x| *x < 0.0) || (s - 1.0).abs() > 1e-6 {
+                return Err(Error::Other(format!("La distribución inicial debe tener {} entradas no negativas que sumen 1", n)));
+            }
+            v.clone()
+        },
+        None => { let mut v = vec![0.0; n]; v[0] = 1.0; v },
+    };
+
+    // 1. Estado estacionario (único si hay una sola clase cerrada)
+    let adj: Vec<Vec<usize>> = q.iter().enumerate()
+        .map(|(i, row)| row.iter().enumerate().filter(|&(j, &x)| j != i && x > TOL).map(|(j, _)| j).collect())
+        .collect();
+    let classes = dtmc::communicating_classes(&adj);
+    let closed_count = classes.iter()
+        .filter(|c| c.iter().all(|&u| adj[u].iter().all(|v| c.contains(v))))
+        .count();
+    let steady_state = if closed_count == 1 { linalg::stationary(&q) } else { None };
+
+    // 2. Transitorio por uniformización: P = I + Q / L
+    let rate = q.iter().enumerate().map(|(i, row)| -row[i]).fold(0.0, f64::max);
+    let mut transient = Vec::with_capacity(req.times.len());
+    for &t in &req.times {
+        if t < 0.0 { return Err(Error::Other(format!("Tiempo negativo {}", t))); }
+        transient.push(uniformization(&q, rate, &initial, t));
+    }
+
+    // 3. Trayectoria (Gillespie)
+    let path = match &req.simulate {
+        Some(sim) => Some(gillespie(&q, sim)?),
+        None => None,
+    };
+
+    Ok(CtmcResponse {
+        n_states: n,
+        generator: q,
+        uniformization_rate: rate,
+        steady_state,
+        steady_state_unique: closed_count == 1,
+        transient,
+        path,
+    })
+}
+
+/// p(t) = sum_k e^{-Lt} (Lt)^k / k! * pi0 P^k, truncando cuando la masa Poisson restante < 1e-12.
+fn uniformization(q: &[Vec<f64>], rate: f64, initial: &[f64], t: f64) -> TransientPoint {
+    let lt = rate * t;
+    if lt == 0.0 {
+        return TransientPoint { t, probabilities: initial.to_vec(), poisson_terms: 1 };
+    }
+    let n = q.len();
+    let p: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| (if i == j { 1.0 } else { 0.0 }) + q[i][j] / rate).collect())
+        .collect();
+
+    let max_terms = (lt + 10.0 * lt.sqrt() + 50.0).ceil() as usize;
+    let mut v = initial.to_vec();
+    let mut out = vec![0.0; n];
+    let mut log_w = -lt; // ln de e^{-Lt} (Lt)^k / k!
+    let mut mass = 0.0;
+    let mut k = 0;
+    loop {
+        let w = log_w.exp();
+        for (o, x) in out.iter_mut().zip(&v) { *o += w * x; }
+        mass += w;
+        k += 1;
+        if (1.0 - mass < 1e-12 && k as f64 > lt) || k >= max_terms { break; }
+        v = linalg::vec_mat(&v, &p);
+        log_w += lt.ln() - (k as f64).ln();
+    }
+    TransientPoint { t, probabilities: out, poisson_terms: k }
+}
+
+/// Algoritmo de Gillespie: tiempo de permanencia Exp(-q_ii) y salto proporcional a q_ij.
+fn gillespie(q: &[Vec<f64>], sim: &CtmcPathRequest) -> Result<CtmcPath, Error> {
+    let n = q.len();
+    if sim.start >= n { return Err(Error::Other(format!("Estado inicial {} fuera de rango", sim.start))); }
+    if sim.max_time <= 0.0 { return Err(Error::Other("max_time debe ser > 0".into())); }
+    let max_events = sim.max_events.unwrap_or(10_000);
+    let mut rng = ChaCha20Rng::seed_from_u64(sim.seed.unwrap_or_else(|| thread_rng().gen()));
+
+    // Distribuciones de salto y de permanencia preparadas una sola vez (None = absorbente)
+    let jumps: Vec<Option<(WeightedIndex<f64>, Exp<f64>)>> = q.iter().enumerate().map(|(i, row)| {
+        let out_rate = -row[i];
+        if out_rate <= TOL { return None; }
+        let weights: Vec<f64> = row.iter().enumerate().map(|(j, &x)| if j == i { 0.0 } else { x }).collect();
+        Some((WeightedIndex::new(&weights).ok()?, Exp::new(out_rate).ok()?))
+    }).collect();
+
+    let mut times = vec![0.0];
+    let mut states = vec![sim.start];
+    let mut time_in_state = vec![0.0; n];
+    let mut state = sim.start;
+    let mut now = 0.0;
+    for _ in 0..max_events {
+        let Some((next, holding)) = &jumps[state] else { break; };
+        let dt = holding.sample(&mut rng);
+        if now + dt >= sim.max_time { break; }
+        time_in_state[state] += dt;
+        now += dt;
+        state = next.sample(&mut rng);
+        times.push(now);
+        states.push(state);
+    }
+    // Último tramo hasta max_time (si se alcanzó max_events, hasta el último salto)
+    let end_time = if states.len() > max_events { now } else { sim.max_time };
+    time_in_state[state] += end_time - now;
+    let total: f64 = time_in_state.iter().sum();
+    if total > 0.0 { for x in time_in_state.iter_mut() { *x /= total; } }
+
+    Ok(CtmcPath { times, states, end_time, time_in_state })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPACITY: usize = 200; // Truncamiento de la cola: con rho <= 2/3 la masa perdida es < 1e-30
+
+    fn birth_death(lambda: f64, mu: f64, servers: usize) -> CtmcResponse {
+        let req = CtmcRequest {
+            generator: None,
+            birth_death: Some(BirthDeath {
+                birth_rates: vec![lambda; CAPACITY],
+                death_rates: (1..=CAPACITY).map(|i| mu * i.min(servers) as f64).collect(),
+            }),
+            initial: None,
+            times: vec![],
+            simulate: None,
+        };
+        analyze(&req).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= 1e-9 * expected.abs().max(1.0), "got {actual}, expected {expected}");
+    }
+
+    /// L = Σ n π_n y Lq = Σ (n - c)+ π_n.
+    fn queue_lengths(pi: &[f64], servers: usize) -> (f64, f64) {
+        let l = pi.iter().enumerate().map(|(n, p)| n as f64 * p).sum();
+        let lq = pi.iter().enumerate().map(|(n, p)| n.saturating_sub(servers) as f64 * p).sum();
+        (l, lq)
+    }
+
+    #[test]
+    fn mm1_matches_closed_forms() {
+        let (lambda, mu) = (1.0, 2.0);
+        let rho = lambda / mu;
+        let pi = birth_death(lambda, mu, 1).steady_state.unwrap();
+        for (n, p) in pi.iter().enumerate().take(20) {
+            assert_close(*p, (1.0 - rho) * rho.powi(n as i32));
+        }
+        let (l, lq) = queue_lengths(&pi, 1);
+        assert_close(l, rho / (1.0 - rho));
+        assert_close(lq, rho * rho / (1.0 - rho));
+        // Little: L = lambda W con W = 1 / (mu - lambda)
+        assert_close(l / lambda, 1.0 / (mu - lambda));
+    }
+
+    #[test]
+    fn mmc_matches_erlang_c() {
+        let (lambda, mu, c) = (2.0, 1.0, 3usize);
+        let a: f64 = lambda / mu;
+        let rho = a / c as f64;
+        let fact = |k: usize| (1..=k).map(|i| i as f64).product::<f64>();
+        let p0 = 1.0 / ((0..c).map(|k| a.powi(k as i32) / fact(k)).sum::<f64>() + a.powi(c as i32) / (fact(c) * (1.0 - rho)));
+        let pi = birth_death(lambda, mu, c).steady_state.unwrap();
+        for (n, p) in pi.iter().enumerate().take(20) {
+            let expected = if n < c {
+                p0 * a.powi(n as i32) / fact(n)
+            } else {
+                p0 * a.powi(n as i32) / (fact(c) * (c as f64).powi((n - c) as i32))
+            };
+            assert_close(*p, expected);
+        }
+        let lq_theory = p0 * a.powi(c as i32) * rho / (fact(c) * (1.0 - rho).powi(2));
+        let (l, lq) = queue_lengths(&pi, c);
+        assert_close(lq, lq_theory);
+        assert_close(l, lq_theory + a);
+        // Little: W = Wq + 1/mu
+        assert_close(l / lambda, lq / lambda + 1.0 / mu);
+    }
+
+    #[test]
+    fn two_state_transient_converges_to_steady_state() {
+        // p_00(t) = b/(a+b) + a/(a+b) e^{-(a+b) t}
+        let (a, b) = (2.0, 0.5);
+        let req = CtmcRequest {
+            generator: Some(vec![vec![-a, a], vec![b, -b]]),
+            birth_death: None,
+            initial: None,
+            times: vec![0.0, 0.5, 50.0],
+            simulate: Some(CtmcPathRequest { start: 0, max_time: 20_000.0, max_events: Some(1_000_000), seed: Some(3) }),
+        };
+        let r = analyze(&req).unwrap();
+        let pi = [b / (a + b), a / (a + b)];
+        let steady = r.steady_state.unwrap();
+        assert_close(steady[0], pi[0]);
+        assert_close(steady[1], pi[1]);
+        assert_eq!(r.uniformization_rate, a);
+
+        assert_eq!(r.transient[0].probabilities, vec![1.0, 0.0]);
+        let p00 = pi[0] + a / (a + b) * (-(a + b) * 0.5f64).exp();
+        assert_close(r.transient[1].probabilities[0], p00);
+        assert_close(r.transient[1].probabilities[1], 1.0 - p00);
+        assert_close(r.transient[2].probabilities[0], pi[0]);
+
+        // Fracción de tiempo simulada en cada estado
+        let path = r.path.unwrap();
+        assert!((path.time_in_state[0] - pi[0]).abs() < 0.01, "{:?}", path.time_in_state);
+    }
+}
@@ -0,0 +1,354 @@
+// src/markov/dtmc.rs
+use serde::{Deserialize, Serialize};
+use rand::prelude::*;
+use rand::distributions::WeightedIndex;
+use rand_chacha::ChaCha20Rng;
+use crate::errors::Error;
+use super::linalg;
+
+const TOL: f64 = 1e-9;
+
+#[derive(Deserialize)]
+pub struct PathRequest {
+    pub start: usize,
+    pub length: usize, // Número de transiciones
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct DtmcRequest {
+    pub matrix: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub initial: Option<Vec<f64>>, // Por defecto, arranca en el estado 0
+    #[serde(default)]
+    pub steps: usize,              // Distribuciones pi_k para k = 0..=steps
+    #[serde(default)]
+    pub simulate: Option<PathRequest>,
+}
+
+#[derive(Serialize)]
+pub struct CommClass {
+    pub states: Vec<usize>,
+    pub closed: bool, // Cerrada = recurrente (cadena finita)
+    pub period: usize,
+}
+
+#[derive(Serialize)]
+pub struct AbsorptionResult {
+    pub transient_states: Vec<usize>,
+    pub absorbing_states: Vec<usize>,
+    pub probabilities: Vec<Vec<f64>>, // B = N R: [transitorio][absorbente]
+    pub expected_steps: Vec<f64>,     // t = N 1
+    pub fundamental_matrix: Vec<Vec<f64>>, // N = (I - Q)^-1
+}
+
+#[derive(Serialize)]
+pub struct DtmcResponse {
+    pub n_states: usize,
+    pub n_step: Vec<Vec<f64>>,
+    pub stationary: Option<Vec<f64>>,
+    pub stationary_unique: bool, // Una sola clase cerrada
+    pub classes: Vec<CommClass>,
+    pub irreducible: bool,
+    pub absorption: Option<AbsorptionResult>,
+    pub path: Option<Vec<usize>>,
+}
+
+/// Valida que la matriz sea cuadrada y estocástica por filas.
+pub fn validate_stochastic(p: &[Vec<f64>]) -> Result<(), Error> {
+    let n = p.len();
+    if n == 0 { return Err(Error::NullOrEmptyInput); }
+    let mut problems = Vec::new();
+    for (i, row) in p.iter().enumerate() {
+        if row.len() != n {
+            problems.push(format!("fila {} tiene {} columnas (se esperaban {})", i, row.len(), n));
+            continue;
+        }
+        if let Some(x) = row.iter().find(|x| !(x.is_finite() && **x >= 0.0 && **x <= 1.0)) {
+            problems.push(format!("fila {} tiene la probabilidad inválida {}", i, x));
+            continue;
+        }
+        let sum: f64 = row.iter().sum();
+        if (sum - 1.0).abs() > 1e-6 {
+            problems.push(format!("fila {} suma {} (debe sumar 1)", i, sum));
+        }
+    }
+    if problems.is_empty() { Ok(()) } else { Err(Error::Other(format!("Matriz no estocástica: {}", problems.join("; ")))) }
+}
+
+/// Clases comunicantes (Tarjan) sobre el grafo de transiciones positivas.
+pub fn communicating_classes(adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        adj: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        out: Vec<Vec<usize>>,
+    }
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+            for &w in &self.adj[v] {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    },
+                    Some(iw) if self.on_stack[w] => self.low[v] = self.low[v].min(iw),
+                    _ => {},
+                }
+            }
+            if Some(self.low[v]) == self.index[v] {
+                let mut class = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    class.push(w);
+                    if w == v { break; }
+                }
+                class.sort_unstable();
+                self.out.push(class);
+            }
+        }
+    }
+
+    let n = adj.len();
+    let mut t = Tarjan {
+        adj, index: vec![None; n], low: vec![0; n], on_stack: vec![false; n],
+        stack: Vec::new(), next: 0, out: Vec::new(),
+    };
+    for v in 0..n {
+        if t.index[v].is_none() { t.visit(v); }
+    }
+    t.out.sort_by_key(|c| c[0]);
+    t.out
+}
+
+/// Periodo de una clase: mcd de (nivel(u) + 1 - nivel(v)) sobre aristas internas u -> v (BFS).
+fn class_period(class: &[usize], adj: &[Vec<usize>], in_class: &[bool]) -> usize {
+    fn gcd(a: usize, b: usize) -> usize { if b == 0 { a } else { gcd(b, a % b) } }
+    let mut level: Vec<Option<usize>> = vec![None; adj.len()];
+    let root = class[0];
+    level[root] = Some(0);
+    let mut queue = std::collections::VecDeque::from([root]);
+    let mut g = 0;
+    while let Some(u) = queue.pop_front() {
+        let lu = level[u].unwrap_or(0);
+        for &v in adj[u].iter().filter(|&&v| in_class[v]) {
+            match level[v] {
+                None => { level[v] = Some(lu + 1); queue.push_back(v); },
+                Some(lv) => g = gcd(g, (lu + 1).abs_diff(lv)),
+            }
+        }
+    }
+    // Clase sin ciclos (estado transitorio aislado): periodo indefinido, se reporta 0
+    g
+}
+
+pub fn analyze(req: &DtmcRequest) -> Result<DtmcResponse, Error> {
+    let p = &req.matrix;
+    validate_stochastic(p)?;
+    let n = p.len();
+
+    // 1. Distribuciones a n pasos
+    let initial = match &req.initial {
+        Some(v) => {
+            if v.len() != n { return Err(Error::Other(format!("La distribución inicial tiene {} estados (se esperaban {})", v.len(), n))); }
+            let s: f64 = v.iter().sum();
+            if v.iter().any(|x| *x < 0.0) || (s - 1.0).abs() > 1e-6 {
+                return Err(Error::Other("La distribución inicial debe ser no negativa y sumar 1".into()));
+            }
+            v.clone()
+        },
+        None => { let mut v = vec![0.0; n]; v[0] = 1.0; v },
+    };
+    let mut n_step = Vec::with_capacity(req.steps + 1);
+    n_step.push(initial);
+    for k in 0..req.steps {
+        let next = linalg::vec_mat(&n_step[k], p);
+        n_step.push(next);
+    }
+
+    // 2. Clases comunicantes y periodicidad
+    let adj: Vec<Vec<usize>> = p.iter()
+        .map(|row| row.iter().enumerate().filter(|(_, &x)| x > TOL).map(|(j, _)| j).collect())
+        .collect();
+    let raw_classes = communicating_classes(&adj);
+    let mut class_of = vec![0usize; n];
+    for (c, class) in raw_classes.iter().enumerate() {
+        for &s in class { class_of[s] = c; }
+    }
+    let classes: Vec<CommClass> = raw_classes.iter().map(|class| {
+        let mut in_class = vec![false; n];
+        for &s in class { in_class[s] = true; }
+        let closed = class.iter().all(|&u| adj[u].iter().all(|&v| in_class[v]));
+        CommClass { states: class.clone(), closed, period: class_period(class, &adj, &in_class) }
+    }).collect();
+    let closed_count = classes.iter().filter(|c| c.closed).count();
+
+    // 3. Distribución estacionaria
+    let a: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| p[i][j] - if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    let stationary = if closed_count == 1 { linalg::stationary(&a) } else { None };
+
+    // 4. Absorción (solo cadenas absorbentes: toda clase cerrada es un estado absorbente)
+    let absorbing_states: Vec<usize> = (0..n).filter(|&i| (p[i][i] - 1.0).abs() < TOL).collect();
+    let is_absorbing_chain = !absorbing_states.is_empty()
+        && classes.iter().filter(|c| c.closed).all(|c| c.states.len() == 1 && absorbing_states.contains(&c.states[0]));
+    let absorption = if is_absorbing_chain {
+        let transient: Vec<usize> = (0..n).filter(|&i| !classes[class_of[i]].closed).collect();
+        absorption_analysis(p, &transient, &absorbing_states)
+    } else {
+        None
+    };
+
+    // 5. Trayectoria simulada
+    let path = match &req.simulate {
+        Some(sim) => Some(simulate_path(p, sim)?),
+        None => None,
+    };
+
+    Ok(DtmcResponse {
+        n_states: n,
+        n_step,
+        stationary,
+        stationary_unique: closed_count == 1,
+        irreducible: classes.len() == 1,
+        classes,
+        absorption,
+        path,
+    })
+}
+
+fn absorption_analysis(p: &[Vec<f64>], transient: &[usize], absorbing: &[usize]) -> Option<AbsorptionResult> {
+    let t = transient.len();
+    if t == 0 {
+        return Some(AbsorptionResult {
+            transient_states: vec![], absorbing_states: absorbing.to_vec(),
+            probabilities: vec![], expected_steps: vec![], fundamental_matrix: vec![],
+        });
+    }
+    // I - Q
+    let i_minus_q: Vec<Vec<f64>> = transient.iter().enumerate()
+        .map(|(a, &i)| transient.iter().enumerate()
+            .map(|(b, &j)| if a == b { 1.0 } else { 0.0 } - p[i][j]).collect())
+        .collect();
+    let fundamental = linalg::invert(&i_minus_q)?;
+    let probabilities = fundamental.iter().map(|row| {
+        absorbing.iter().map(|&k| row.iter().zip(transient).map(|(nij, &j)| nij * p[j][k]).sum()).collect()
+    }).collect();
+    let expected_steps = fundamental.iter().map(|row| row.iter().sum()).collect();
+    Some(AbsorptionResult {
+        transient_states: transient.to_vec(),
+        absorbing_states: absorbing.to_vec(),
+        probabilities,
+        expected_steps,
+        fundamental_matrix: fundamental,
+    })
+}
+
+fn simulate_path(p: &[Vec<f64>], sim: &PathRequest) -> Result<Vec<usize>, Error> {
+    if sim.start >= p.len() { return Err(Error::Other(format!("Estado inicial {} fuera de rango", sim.start))); }
+    let mut rng = ChaCha20Rng::seed_from_u64(sim.seed.unwrap_or_else(|| thread_rng().gen()));
+    // Una distribución por fila, preparada una sola vez
+    let rows: Vec<WeightedIndex<f64>> = p.iter()
+        .map(|row| WeightedIndex::new(row).map_err(|e| Error::Other(format!("Fila inválida: {}", e))))
+        .collect::<Result<_, _>>()?;
+    let mut path = Vec::with_capacity(sim.length + 1);
+    let mut state = sim.start;
+    path.push(state);
+    for _ in 0..sim.length {
+        state = rows[state].sample(&mut rng);
+        path.push(state);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(matrix: Vec<Vec<f64>>) -> DtmcResponse {
+        analyze(&DtmcRequest { matrix, initial: None, steps: 0, simulate: None }).unwrap()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-10, "{actual:?} vs {expected:?}");
+        }
+    }
+
+    /// Clases como conjuntos ordenados, para no depender del orden de Tarjan.
+    fn sorted_classes(r: &DtmcResponse) -> Vec<(Vec<usize>, bool, usize)> {
+        let mut classes: Vec<_> = r.classes.iter().map(|c| {
+            let mut states = c.states.clone();
+            states.sort_unstable();
+            (states, c.closed, c.period)
+        }).collect();
+        classes.sort();
+        classes
+    }
+
+    #[test]
+    fn two_state_chain_stationary_distribution() {
+        // π P = π con P = [[0.9, 0.1], [0.5, 0.5]]: π = (5/6, 1/6)
+        let r = run(vec![vec![0.9, 0.1], vec![0.5, 0.5]]);
+        assert!(r.irreducible && r.stationary_unique);
+        assert_eq!(sorted_classes(&r), vec![(vec![0, 1], true, 1)]);
+        assert_close(&r.stationary.unwrap(), &[5.0 / 6.0, 1.0 / 6.0]);
+        assert!(r.absorption.is_none());
+    }
+
+    #[test]
+    fn three_cycle_has_period_three() {
+        let req = DtmcRequest {
+            matrix: vec![vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0], vec![1.0, 0.0, 0.0]],
+            initial: None,
+            steps: 3,
+            simulate: Some(PathRequest { start: 1, length: 4, seed: Some(1) }),
+        };
+        let r = analyze(&req).unwrap();
+        assert_eq!(sorted_classes(&r), vec![(vec![0, 1, 2], true, 3)]);
+        assert_close(&r.stationary.unwrap(), &[1.0 / 3.0; 3]);
+        assert_close(&r.n_step[3], &[1.0, 0.0, 0.0]);
+        assert_eq!(r.path.unwrap(), vec![1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn gamblers_ruin_absorption() {
+        // Capital 0..=4 con p = 0.4 de ganar: r = q/p = 1.5
+        let (p, q) = (0.4, 0.6);
+        let matrix = (0..5).map(|i| {
+            let mut row = vec![0.0; 5];
+            if i == 0 || i == 4 { row[i] = 1.0; } else { row[i + 1] = p; row[i - 1] = q; }
+            row
+        }).collect();
+        let r = run(matrix);
+        assert!(!r.irreducible && !r.stationary_unique && r.stationary.is_none());
+        assert_eq!(sorted_classes(&r), vec![(vec![0], true, 1), (vec![1, 2, 3], false, 2), (vec![4], true, 1)]);
+
+        let abs = r.absorption.unwrap();
+        assert_eq!(abs.transient_states, vec![1, 2, 3]);
+        assert_eq!(abs.absorbing_states, vec![0, 4]);
+        let ratio: f64 = q / p;
+        for (row, k) in abs.probabilities.iter().zip(1..) {
+            // Ruina (r^k - r^N) / (1 - r^N); duración k/(q-p) - N/(q-p) · (1 - r^k) / (1 - r^N)
+            let ruin = (ratio.powi(k) - ratio.powi(4)) / (1.0 - ratio.powi(4));
+            assert_close(row, &[ruin, 1.0 - ruin]);
+            let steps = k as f64 / (q - p) - 4.0 / (q - p) * (1.0 - ratio.powi(k)) / (1.0 - ratio.powi(4));
+            assert_close(&[abs.expected_steps[k as usize - 1]], &[steps]);
+        }
+    }
+
+    #[test]
+    fn rejects_non_stochastic_rows() {
+        assert!(analyze(&DtmcRequest { matrix: vec![vec![0.5, 0.4], vec![0.0, 1.0]], initial: None, steps: 0, simulate: None }).is_err());
+        assert!(analyze(&DtmcRequest { matrix: vec![vec![1.0, 0.0]], initial: None, steps: 0, simulate: None }).is_err());
+    }
+}
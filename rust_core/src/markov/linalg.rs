@@ -0,0 +1,67 @@
+// src/markov/linalg.rs
+// Álgebra lineal mínima para cadenas pequeñas (matrices densas).
+
+/// Resuelve A x = b por eliminación gaussiana con pivoteo parcial.
+/// Devuelve None si la matriz es (numéricamente) singular.
+pub fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap_or(std::cmp::Ordering::Equal))?;
+        if a[pivot][col].abs() < 1e-12 { return None; }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            if factor == 0.0 { continue; }
+            for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) { *x -= factor * p; }
+            b[col + 1 + offset] -= factor * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let s: f64 = ((row + 1)..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - s) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Inversa por columnas (resolviendo A x = e_j).
+pub fn invert(a: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut inv = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        let mut e = vec![0.0; n];
+        e[j] = 1.0;
+        let col = solve(a.to_vec(), e)?;
+        for i in 0..n { inv[i][j] = col[i]; }
+    }
+    Some(inv)
+}
+
+/// Vector fila por matriz: v P
+pub fn vec_mat(v: &[f64], m: &[Vec<f64>]) -> Vec<f64> {
+    let n = m.first().map_or(0, |r| r.len());
+    let mut out = vec![0.0; n];
+    for (vi, row) in v.iter().zip(m) {
+        if *vi == 0.0 { continue; }
+        for (o, p) in out.iter_mut().zip(row) { *o += vi * p; }
+    }
+    out
+}
+
+/// Distribución estacionaria: resuelve pi A = 0 con sum(pi) = 1, donde A es
+/// P - I (tiempo discreto) o el generador Q (tiempo continuo).
+pub fn stationary(a: &[Vec<f64>]) -> Option<Vec<f64>> {
+    let n = a.len();
+    // Sistema transpuesto A^T pi^T = 0, reemplazando la última ecuación por la normalización
+    let mut sys = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..n { sys[i][j] = a[j][i]; }
+    }
+    sys[n - 1] = vec![1.0; n];
+    let mut rhs = vec![0.0; n];
+    rhs[n - 1] = 1.0;
+    solve(sys, rhs).map(|pi| pi.into_iter().map(|p| if p.abs() < 1e-15 { 0.0 } else { p }).collect())
+}
@@ -0,0 +1,15 @@
+// src/markov/mod.rs
+pub mod linalg;
+pub mod dtmc;
+pub mod ctmc;
+
+use std::ffi::c_char;
+use crate::json_helpers::run_json;
+
+pub fn run_dtmc(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, dtmc::analyze)
+}
+
+pub fn run_ctmc(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, ctmc::analyze)
+}
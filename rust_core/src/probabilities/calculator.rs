@@ -0,0 +1,190 @@
+// src/probabilities/calculator.rs
+// Calculadora de probabilidades sobre distribuciones teóricas: áreas bajo la curva,
+// densidad puntual, momentos y la curva muestreada para sombrear la región pedida.
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::json_helpers::run_json;
+use crate::probabilities::distribution::{Dist, DistInput, Distribution};
+
+// Tope de barras en la curva de una discreta
+const MAX_DISCRETE_POINTS: u64 = 2000;
+
+fn default_curve_points() -> usize {
+    200
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Area {
+    LessEqual,    // P(X <= a)
+    GreaterEqual, // P(X >= a)
+    Between,      // P(a <= X <= b)
+}
+
+#[derive(Deserialize)]
+pub struct ProbabilityRequest {
+    // Mismo formato de distribución que calculate_inverse_cdf
+    #[serde(flatten)]
+    pub distribution: DistInput,
+    pub a: f64,
+    #[serde(default)]
+    pub b: Option<f64>,
+    #[serde(default)]
+    pub x: Option<f64>, // Punto para la densidad; por defecto a
+    #[serde(default)]
+    pub area: Option<Area>, // Región a sombrear; por defecto Between si hay b, si no LessEqual
+    #[serde(default = "default_curve_points")]
+    pub curve_points: usize,
+}
+
+#[derive(Serialize)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub y: f64,
+    pub shaded: bool,
+}
+
+#[derive(Serialize)]
+pub struct ProbabilityResponse {
+    pub is_discrete: bool,
+    pub less_equal: f64,
+    pub greater_equal: f64,
+    pub between: Option<f64>,
+    pub density: f64, // f(x) en continuas, P(X = x) en discretas
+    pub mean: f64,
+    pub variance: f64,
+    pub skewness: f64,
+    pub kurtosis_excess: f64,
+    pub mode: Option<f64>,
+    pub median: f64,
+    pub area: Area,
+    pub area_probability: f64,
+    pub curve: Vec<CurvePoint>,
+}
+
+pub fn calculate(req: &ProbabilityRequest) -> Result<ProbabilityResponse, Error> {
+    let dist = req.distribution.resolve().map_err(Error::Other)?;
+    let discrete = dist.is_discrete();
+    if !req.a.is_finite() || req.b.is_some_and(|b| !b.is_finite()) {
+        return Err(Error::Other("Los límites a y b deben ser finitos".into()));
+    }
+    let (a, b) = match req.b {
+        Some(b) if b < req.a => (b, Some(req.a)),
+        b => (req.a, b),
+    };
+
+    // P(X < x): en las discretas se descuenta el salto en x (sirve también para tablas con
+    // valores no enteros, donde ceil(x) - 1 no es el valor anterior del soporte)
+    let below = |x: f64| if discrete { (dist.cdf(x) - dist.pdf(x)).max(0.0) } else { dist.cdf(x) };
+    let less_equal = dist.cdf(a);
+    let greater_equal = 1.0 - below(a);
+    let between = b.map(|b| (dist.cdf(b) - below(a)).max(0.0));
+
+    let area = req.area.unwrap_or(if b.is_some() { Area::Between } else { Area::LessEqual });
+    let area_probability = match area {
+        Area::LessEqual => less_equal,
+        Area::GreaterEqual => greater_equal,
+        Area::Between => between.ok_or_else(|| Error::Other("El área 'between' requiere b".into()))?,
+    };
+    let shaded = |x: f64| match area {
+        Area::LessEqual => x <= a,
+        Area::GreaterEqual => x >= a,
+        Area::Between => x >= a && x <= b.unwrap_or(a),
+    };
+
+    // Rango de la curva: cuantiles extremos, ampliado para incluir a y b si caen en el soporte
+    let (sup_lo, sup_hi) = dist.support();
+    let mut lo = if sup_lo.is_finite() { sup_lo } else { dist.quantile(0.001) };
+    let mut hi = if sup_hi.is_finite() { sup_hi } else { dist.quantile(0.999) };
+    // max/min en lugar de clamp: con cuantiles NaN o invertidos clamp entra en pánico, y
+    // f64::max/min descartan el NaN (a y b quedan sin recortar)
+    let (q_lo, q_hi) = (dist.quantile(1e-6), dist.quantile(1.0 - 1e-6));
+    for v in std::iter::once(a).chain(b) {
+        let v = v.max(q_lo).min(q_hi);
+        lo = lo.min(v);
+        hi = hi.max(v);
+    }
+
+    let curve = if let Dist::Discrete { ref values, .. } = dist {
+        // Tabla del usuario: una barra por valor (ya ordenados), sean enteros o no
+        let mut xs = values.clone();
+        xs.dedup();
+        xs.into_iter().map(|x| CurvePoint { x, y: dist.pdf(x), shaded: shaded(x) }).collect()
+    } else if discrete {
+        let (k_lo, k_hi) = (lo.max(0.0) as u64, hi as u64);
+        let k_hi = k_hi.min(k_lo + MAX_DISCRETE_POINTS);
+        (k_lo..=k_hi).map(|k| {
+            let x = k as f64;
+            CurvePoint { x, y: dist.pdf(x), shaded: shaded(x) }
+        }).collect()
+    } else {
+        let n = req.curve_points.max(2);
+        let step = (hi - lo) / (n - 1) as f64;
+        (0..n).map(|i| {
+            let x = lo + step * i as f64;
+            CurvePoint { x, y: dist.pdf(x), shaded: shaded(x) }
+        }).collect()
+    };
+
+    let moments = dist.moments();
+    Ok(ProbabilityResponse {
+        is_discrete: discrete,
+        less_equal,
+        greater_equal,
+        between,
+        density: dist.pdf(req.x.unwrap_or(req.a)),
+        mean: moments.mean,
+        variance: moments.variance,
+        skewness: moments.skewness,
+        kurtosis_excess: moments.kurtosis_excess,
+        mode: dist.mode(),
+        median: dist.median(),
+        area,
+        area_probability,
+        curve,
+    })
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn calculate_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, calculate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> ProbabilityResponse {
+        calculate(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn discrete_table_with_fractional_values() {
+        let table = r#""distribution": {"type": "discrete", "values": [0.75, -1.5, 0.25], "probabilities": [0.25, 0.25, 0.5]}"#;
+        let r = request(&format!(r#"{{{table}, "a": 0.75, "area": "greater_equal"}}"#));
+        assert_eq!(r.less_equal, 1.0);
+        assert_eq!(r.greater_equal, 0.25);
+        assert_eq!(r.area_probability, 0.25);
+        let xs: Vec<f64> = r.curve.iter().map(|p| p.x).collect();
+        assert_eq!(xs, vec![-1.5, 0.25, 0.75]);
+        assert_eq!(r.curve.iter().map(|p| p.shaded).collect::<Vec<_>>(), vec![false, false, true]);
+
+        let r = request(&format!(r#"{{{table}, "a": 0.25, "b": 0.75}}"#));
+        assert_eq!(r.between, Some(0.75));
+        assert_eq!(r.greater_equal, 0.75);
+        let r = request(&format!(r#"{{{table}, "a": 0.3, "b": 0.75}}"#));
+        assert_eq!(r.between, Some(0.25));
+    }
+
+    #[test]
+    fn integer_distributions_keep_the_point_mass_at_a() {
+        let r = request(r#"{"distribution": {"type": "poisson", "lambda": 3}, "a": 2, "b": 4}"#);
+        let pois = Dist::Poisson { lambda: 3.0 };
+        assert!((r.greater_equal - (1.0 - pois.cdf(1.0))).abs() < 1e-12);
+        assert!((r.between.unwrap() - (pois.cdf(4.0) - pois.cdf(1.0))).abs() < 1e-12);
+        // a no entero: P(X >= 2.5) = P(X >= 3)
+        let r = request(r#"{"distribution": {"type": "poisson", "lambda": 3}, "a": 2.5}"#);
+        assert!((r.greater_equal - (1.0 - pois.cdf(2.0))).abs() < 1e-12);
+    }
+}
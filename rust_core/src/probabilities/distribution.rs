@@ -0,0 +1,1168 @@
+// src/probabilities/distribution.rs
+// Distribuciones univariadas en un solo lugar: validación de parámetros, densidad, CDF,
+// cuantiles, momentos, log-verosimilitud y muestreo. Agregar una distribución solo toca este
+// archivo: su variante en `Dist`, su forma JSON en `DistSpec` (con las dos conversiones) y sus
+// ramas aquí. Los simuladores la reciben sin cambios a través de `DistType::Param`.
+use rand::distributions::{Bernoulli, Open01, WeightedIndex};
+use rand::Rng;
+use rand_distr::Distribution as _;
+use rand_distr::{
+    Beta, Binomial, Cauchy, ChiSquared, Exp, FisherF, Gamma, Geometric, Gumbel, Hypergeometric,
+    LogNormal, Normal, Pareto, Pert, Poisson, StudentT, Triangular, Uniform, Weibull,
+};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, SQRT_2};
+use crate::simulations::montecarlo::models::EmpiricalMethod;
+use crate::probabilities::special::{
+    beta_inc, beta_inc_inv, gamma_p, gamma_p_inv, gamma_q, ln_beta, ln_gamma, standard_normal_cdf,
+    standard_normal_quantile,
+};
+
+/// Media, varianza, asimetría y exceso de curtosis. NaN/inf cuando el momento no existe.
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct Moments {
+    pub mean: f64,
+    pub variance: f64,
+    pub skewness: f64,
+    pub kurtosis_excess: f64,
+}
+
+impl Moments {
+    fn new(mean: f64, variance: f64, skewness: f64, kurtosis_excess: f64) -> Self {
+        Moments { mean, variance, skewness, kurtosis_excess }
+    }
+
+    pub fn std(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+/// Interfaz común de una distribución univariada.
+pub trait Distribution {
+    /// Revisa que los parámetros definan una distribución válida.
+    fn validate(&self) -> Result<(), String>;
+    fn is_discrete(&self) -> bool;
+    /// Soporte [inferior, superior] (pueden ser infinitos).
+    fn support(&self) -> (f64, f64);
+    /// Densidad f(x) en las continuas; P(X = x) en las discretas.
+    fn pdf(&self, x: f64) -> f64;
+    /// ln f(x), sin subdesbordamiento en las colas; -inf fuera del soporte.
+    fn ln_pdf(&self, x: f64) -> f64 {
+        self.pdf(x).ln()
+    }
+    /// F(x) = P(X <= x).
+    fn cdf(&self, x: f64) -> f64;
+    /// x_p = F^-1(p). En las discretas, el menor k con F(k) >= p.
+    fn quantile(&self, p: f64) -> f64;
+    fn moments(&self) -> Moments;
+    /// Moda (la menor si hay varias). None si no es única.
+    fn mode(&self) -> Option<f64>;
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64;
+
+    fn median(&self) -> f64 {
+        self.quantile(0.5)
+    }
+
+    /// Σ ln f(x_i); -inf si los parámetros no son válidos o algún dato cae fuera del soporte.
+    fn ln_likelihood(&self, data: &[f64]) -> f64 {
+        if self.validate().is_err() { return f64::NEG_INFINITY; }
+        data.iter().map(|&x| self.ln_pdf(x)).sum()
+    }
+}
+
+/// Familias paramétricas soportadas por el crate. En JSON usa el esquema de `DistSpec`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "DistSpec", into = "DistSpec")]
+pub enum Dist {
+    // Continuas
+    Normal { mean: f64, std: f64 },
+    Exponential { beta: f64 }, // Media beta
+    Uniform { min: f64, max: f64 },
+    Lognormal { mu: f64, sigma: f64 }, // Parámetros de ln(X)
+    Gamma { shape: f64, scale: f64 },
+    Beta { alpha: f64, beta: f64 },
+    Weibull { shape: f64, scale: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+    Pert { min: f64, mode: f64, max: f64 },
+    Cauchy { location: f64, scale: f64 },
+    StudentT { df: f64 },
+    ChiSquare { df: f64 },
+    F { d1: f64, d2: f64 },
+    Logistic { location: f64, scale: f64 },
+    Gumbel { location: f64, scale: f64 }, // De máximos
+    Pareto { scale: f64, shape: f64 }, // scale = x_m (mínimo del soporte)
+    ScaledBeta { alpha: f64, beta: f64, min: f64, max: f64 }, // Beta reescalada a [min, max]
+    // Discretas
+    Poisson { lambda: f64 },
+    Binomial { n: u64, p: f64 },
+    Geometric { p: f64 }, // Fracasos antes del primer éxito
+    Bernoulli { p: f64 },
+    NegativeBinomial { r: f64, p: f64 }, // Fracasos antes del r-ésimo éxito
+    Hypergeometric { population: u64, successes: u64, draws: u64 },
+    Discrete { values: Vec<f64>, probabilities: Vec<f64> }, // Tabla del usuario, ordenada por valor
+}
+
+fn positive(value: f64, name: &str) -> Result<(), String> {
+    if value > 0.0 && value.is_finite() { Ok(()) } else { Err(format!("{} debe ser > 0", name)) }
+}
+
+fn check_probability(p: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&p) { Ok(()) } else { Err(format!("Probabilidad p = {} fuera de [0, 1]", p)) }
+}
+
+fn check_open_probability(p: f64) -> Result<(), String> {
+    if p > 0.0 && p <= 1.0 { Ok(()) } else { Err(format!("Probabilidad p = {} fuera de (0, 1]", p)) }
+}
+
+fn check_mode_range(min: f64, mode: f64, max: f64) -> Result<(), String> {
+    if min >= max { return Err("Min >= Max".to_string()); }
+    if mode < min || mode > max { return Err(format!("Moda {} fuera de [{}, {}]", mode, min, max)); }
+    Ok(())
+}
+
+impl Dist {
+    /// Construye y valida la distribución a partir del nombre y los parámetros posicionales
+    /// de la API (param1..param3). Ojo: la normal recibe la varianza, no la desviación.
+    pub fn from_params(dist_type: &str, p1: f64, p2: f64, p3: f64) -> Result<Self, String> {
+        let dist = match dist_type {
+            "normal" => {
+                if p2 < 0.0 { return Err("Varianza negativa".to_string()); }
+                Dist::Normal { mean: p1, std: p2.sqrt() }
+            },
+            "exponential" => Dist::Exponential { beta: p1 },
+            "uniform" => Dist::Uniform { min: p1, max: p2 },
+            "lognormal" => Dist::Lognormal { mu: p1, sigma: p2 },
+            "gamma" => Dist::Gamma { shape: p1, scale: p2 },
+            "beta" => Dist::Beta { alpha: p1, beta: p2 },
+            "weibull" => Dist::Weibull { shape: p1, scale: p2 },
+            "triangular" => Dist::Triangular { min: p1, mode: p2, max: p3 },
+            "pert" => Dist::Pert { min: p1, mode: p2, max: p3 },
+            "cauchy" => Dist::Cauchy { location: p1, scale: p2 },
+            "t" | "student_t" => Dist::StudentT { df: p1 },
+            "chi_square" | "chi2" => Dist::ChiSquare { df: p1 },
+            "f" => Dist::F { d1: p1, d2: p2 },
+            "logistic" => Dist::Logistic { location: p1, scale: p2 },
+            "gumbel" => Dist::Gumbel { location: p1, scale: p2 },
+            "pareto" => Dist::Pareto { scale: p1, shape: p2 },
+            "poisson" => Dist::Poisson { lambda: p1 },
+            "binomial" => {
+                if p1 < 1.0 || p1.fract() != 0.0 { return Err("n debe ser un entero >= 1".to_string()); }
+                Dist::Binomial { n: p1 as u64, p: p2 }
+            },
+            "geometric" => Dist::Geometric { p: p1 },
+            "bernoulli" => Dist::Bernoulli { p: p1 },
+            "negative_binomial" => Dist::NegativeBinomial { r: p1, p: p2 },
+            "hypergeometric" => {
+                if [p1, p2, p3].iter().any(|v| *v < 0.0 || v.fract() != 0.0) {
+                    return Err("Población, éxitos y extracciones deben ser enteros >= 0".to_string());
+                }
+                Dist::Hypergeometric { population: p1 as u64, successes: p2 as u64, draws: p3 as u64 }
+            },
+            _ => return Err(format!("Distribución '{}' no soportada", dist_type)),
+        };
+        dist.validate()?;
+        Ok(dist)
+    }
+
+    /// Traduce el `best_fit` de analysis.rs (name + params) a la distribución equivalente.
+    /// Ojo: la normal ajustada trae [media, desviación], no varianza.
+    pub fn from_fit(fit_name: &str, params: &[f64]) -> Result<Self, String> {
+        let expect = |n: usize| -> Result<(), String> {
+            if params.len() != n {
+                return Err(format!("El ajuste '{}' requiere {} parámetros y se recibieron {}", fit_name, n, params.len()));
+            }
+            Ok(())
+        };
+        let dist = match fit_name {
+            "normal" => {
+                expect(2)?;
+                // Un ajuste con desviación 0 es degenerado (verosimilitud infinita)
+                positive(params[1], "La desviación estándar")?;
+                Dist::Normal { mean: params[0], std: params[1] }
+            },
+            "exponential" => {
+                expect(1)?;
+                Dist::Exponential { beta: params[0] }
+            },
+            "lognormal" => {
+                expect(2)?;
+                Dist::Lognormal { mu: params[0], sigma: params[1] }
+            },
+            "uniform" => {
+                expect(2)?;
+                Dist::Uniform { min: params[0], max: params[1] }
+            },
+            "gamma" => {
+                expect(2)?;
+                Dist::Gamma { shape: params[0], scale: params[1] }
+            },
+            // Erlang(k, beta) = Gamma(k, beta)
+            "erlang" => {
+                expect(2)?;
+                if params[0] < 1.0 || params[0].fract() != 0.0 { return Err("k debe ser un entero >= 1".to_string()); }
+                Dist::Gamma { shape: params[0], scale: params[1] }
+            },
+            "weibull" => {
+                expect(2)?;
+                Dist::Weibull { shape: params[0], scale: params[1] }
+            },
+            // [alfa, beta, min, max]: beta ajustada sobre los datos reescalados a [0, 1]
+            "beta" => {
+                expect(4)?;
+                Dist::ScaledBeta { alpha: params[0], beta: params[1], min: params[2], max: params[3] }
+            },
+            "logistic" => {
+                expect(2)?;
+                Dist::Logistic { location: params[0], scale: params[1] }
+            },
+            "gumbel" => {
+                expect(2)?;
+                Dist::Gumbel { location: params[0], scale: params[1] }
+            },
+            "pareto" => {
+                expect(2)?;
+                Dist::Pareto { scale: params[0], shape: params[1] }
+            },
+            "triangular" => {
+                expect(3)?;
+                Dist::Triangular { min: params[0], mode: params[1], max: params[2] }
+            },
+            // Discretas (datos de conteo)
+            "poisson" => {
+                expect(1)?;
+                Dist::Poisson { lambda: params[0] }
+            },
+            "binomial" => {
+                expect(2)?;
+                if params[0] < 1.0 || params[0].fract() != 0.0 { return Err("n debe ser un entero >= 1".to_string()); }
+                Dist::Binomial { n: params[0] as u64, p: params[1] }
+            },
+            "geometric" => {
+                expect(1)?;
+                Dist::Geometric { p: params[0] }
+            },
+            "negative_binomial" => {
+                expect(2)?;
+                Dist::NegativeBinomial { r: params[0], p: params[1] }
+            },
+            _ => return Err(format!("Ajuste '{}' no soportado", fit_name)),
+        };
+        dist.validate()?;
+        Ok(dist)
+    }
+
+    /// Muestreador preparado (constantes precalculadas) para los bucles de simulación.
+    /// Requiere una distribución válida.
+    pub fn sampler(&self) -> Sampler {
+        match *self {
+            Dist::Normal { mean, std } => Sampler::Normal(Normal::new(mean, std).unwrap()),
+            Dist::Exponential { beta } => Sampler::Exponential(Exp::new(1.0 / beta).unwrap()),
+            Dist::Uniform { min, max } => Sampler::Uniform(Uniform::new_inclusive(min, max)),
+            Dist::Lognormal { mu, sigma } => Sampler::Lognormal(LogNormal::new(mu, sigma).unwrap()),
+            Dist::Gamma { shape, scale } => Sampler::Gamma(Gamma::new(shape, scale).unwrap()),
+            Dist::Beta { alpha, beta } => Sampler::Beta(Beta::new(alpha, beta).unwrap()),
+            Dist::Weibull { shape, scale } => Sampler::Weibull(Weibull::new(scale, shape).unwrap()),
+            Dist::Triangular { min, mode, max } => Sampler::Triangular(Triangular::new(min, max, mode).unwrap()),
+            Dist::Pert { min, mode, max } => Sampler::Pert(Pert::new(min, max, mode).unwrap()),
+            Dist::Cauchy { location, scale } => Sampler::Cauchy(Cauchy::new(location, scale).unwrap()),
+            Dist::StudentT { df } => Sampler::StudentT(StudentT::new(df).unwrap()),
+            Dist::ChiSquare { df } => Sampler::ChiSquare(ChiSquared::new(df).unwrap()),
+            Dist::F { d1, d2 } => Sampler::F(FisherF::new(d1, d2).unwrap()),
+            Dist::Logistic { location, scale } => Sampler::Logistic(location, scale),
+            Dist::Gumbel { location, scale } => Sampler::Gumbel(Gumbel::new(location, scale).unwrap()),
+            Dist::Pareto { scale, shape } => Sampler::Pareto(Pareto::new(scale, shape).unwrap()),
+            Dist::ScaledBeta { alpha, beta, min, max } => {
+                Sampler::ScaledBeta(Beta::new(alpha, beta).unwrap(), min, max - min)
+            },
+            Dist::Poisson { lambda } => Sampler::Poisson(Poisson::new(lambda).unwrap()),
+            Dist::Binomial { n, p } => Sampler::Binomial(Binomial::new(n, p).unwrap()),
+            Dist::Geometric { p } => Sampler::Geometric(Geometric::new(p).unwrap()),
+            Dist::Bernoulli { p } => Sampler::Bernoulli(Bernoulli::new(p).unwrap()),
+            Dist::NegativeBinomial { r, p } => {
+                if p == 1.0 { return Sampler::Constant(0.0); }
+                Sampler::NegativeBinomial(Gamma::new(r, (1.0 - p) / p).unwrap())
+            },
+            Dist::Hypergeometric { population, successes, draws } => {
+                Sampler::Hypergeometric(Hypergeometric::new(population, successes, draws).unwrap())
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                Sampler::Table(WeightedIndex::new(probabilities).unwrap(), values.clone())
+            },
+        }
+    }
+
+    /// Tabla discreta con los pares (valor, probabilidad) ordenados por valor, para que la
+    /// acumulada de `quantile` recorra el soporte en orden (F^-1 coherente con `cdf`).
+    pub fn discrete(values: Vec<f64>, probabilities: Vec<f64>) -> Dist {
+        if values.len() != probabilities.len() {
+            return Dist::Discrete { values, probabilities }; // validate() informa el error
+        }
+        let mut pairs: Vec<(f64, f64)> = values.into_iter().zip(probabilities).collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (values, probabilities) = pairs.into_iter().unzip();
+        Dist::Discrete { values, probabilities }
+    }
+
+    /// Parámetros (alfa, beta) de la beta subyacente de la PERT.
+    fn pert_shape(min: f64, mode: f64, max: f64) -> (f64, f64) {
+        let range = max - min;
+        (1.0 + 4.0 * (mode - min) / range, 1.0 + 4.0 * (max - mode) / range)
+    }
+}
+
+/// Esquema JSON único de las distribuciones, etiquetado por `type`:
+/// `{"type": "normal", "mean": 0, "std": 1}`, `{"type": "gamma", "shape": 2, "scale": 3}`, ...
+/// La normal acepta `std` o `variance` (uno de los dos, explícito).
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DistSpec {
+    Normal {
+        mean: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        std: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variance: Option<f64>,
+    },
+    Exponential { beta: f64 },
+    Uniform { min: f64, max: f64 },
+    Lognormal { mu: f64, sigma: f64 },
+    Gamma { shape: f64, scale: f64 },
+    Beta { alpha: f64, beta: f64 },
+    Weibull { shape: f64, scale: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+    Pert { min: f64, mode: f64, max: f64 },
+    Erlang { k: u32, beta: f64 }, // Gamma con forma entera
+    Cauchy { location: f64, scale: f64 },
+    #[serde(alias = "t")]
+    StudentT { df: f64 },
+    #[serde(alias = "chi2")]
+    ChiSquare { df: f64 },
+    F { d1: f64, d2: f64 },
+    Logistic { location: f64, scale: f64 },
+    Gumbel { location: f64, scale: f64 },
+    Pareto { scale: f64, shape: f64 },
+    ScaledBeta { alpha: f64, beta: f64, min: f64, max: f64 },
+    Poisson { lambda: f64 },
+    Binomial { n: u64, p: f64 },
+    Geometric { p: f64 },
+    Bernoulli { p: f64 },
+    NegativeBinomial { r: f64, p: f64 },
+    Hypergeometric { population: u64, successes: u64, draws: u64 },
+    Discrete { values: Vec<f64>, probabilities: Vec<f64> },
+    // No paramétricas: solo las aceptan los simuladores (DistType)
+    Empirical {
+        data: Vec<f64>,
+        #[serde(default)]
+        method: EmpiricalMethod,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bandwidth: Option<f64>,
+    },
+    // `best_fit` tal cual lo devuelve analyze_distribution_json (name + params)
+    Fitted { name: String, params: Vec<f64> },
+}
+
+impl DistSpec {
+    /// Desviación estándar de una normal dada por `std` o por `variance`.
+    pub fn normal_std(std: Option<f64>, variance: Option<f64>) -> Result<f64, String> {
+        match (std, variance) {
+            (Some(_), Some(_)) => Err("La normal admite std o variance, no ambos".to_string()),
+            (Some(std), None) => Ok(std),
+            (None, Some(v)) if v < 0.0 => Err("Varianza negativa".to_string()),
+            (None, Some(v)) => Ok(v.sqrt()),
+            (None, None) => Err("La normal requiere std o variance".to_string()),
+        }
+    }
+}
+
+impl TryFrom<DistSpec> for Dist {
+    type Error = String;
+
+    fn try_from(spec: DistSpec) -> Result<Self, String> {
+        let dist = match spec {
+            DistSpec::Normal { mean, std, variance } => Dist::Normal { mean, std: DistSpec::normal_std(std, variance)? },
+            DistSpec::Exponential { beta } => Dist::Exponential { beta },
+            DistSpec::Uniform { min, max } => Dist::Uniform { min, max },
+            DistSpec::Lognormal { mu, sigma } => Dist::Lognormal { mu, sigma },
+            DistSpec::Gamma { shape, scale } => Dist::Gamma { shape, scale },
+            DistSpec::Beta { alpha, beta } => Dist::Beta { alpha, beta },
+            DistSpec::Weibull { shape, scale } => Dist::Weibull { shape, scale },
+            DistSpec::Triangular { min, mode, max } => Dist::Triangular { min, mode, max },
+            DistSpec::Pert { min, mode, max } => Dist::Pert { min, mode, max },
+            DistSpec::Erlang { k, beta } => {
+                if k == 0 { return Err("k debe ser >= 1".to_string()); }
+                Dist::Gamma { shape: k as f64, scale: beta }
+            },
+            DistSpec::Cauchy { location, scale } => Dist::Cauchy { location, scale },
+            DistSpec::StudentT { df } => Dist::StudentT { df },
+            DistSpec::ChiSquare { df } => Dist::ChiSquare { df },
+            DistSpec::F { d1, d2 } => Dist::F { d1, d2 },
+            DistSpec::Logistic { location, scale } => Dist::Logistic { location, scale },
+            DistSpec::Gumbel { location, scale } => Dist::Gumbel { location, scale },
+            DistSpec::Pareto { scale, shape } => Dist::Pareto { scale, shape },
+            DistSpec::ScaledBeta { alpha, beta, min, max } => Dist::ScaledBeta { alpha, beta, min, max },
+            DistSpec::Poisson { lambda } => Dist::Poisson { lambda },
+            DistSpec::Binomial { n, p } => Dist::Binomial { n, p },
+            DistSpec::Geometric { p } => Dist::Geometric { p },
+            DistSpec::Bernoulli { p } => Dist::Bernoulli { p },
+            DistSpec::NegativeBinomial { r, p } => Dist::NegativeBinomial { r, p },
+            DistSpec::Hypergeometric { population, successes, draws } => {
+                Dist::Hypergeometric { population, successes, draws }
+            },
+            DistSpec::Discrete { values, probabilities } => Dist::discrete(values, probabilities),
+            DistSpec::Empirical { .. } => {
+                return Err("Los datos empíricos no definen una distribución paramétrica".to_string());
+            },
+            DistSpec::Fitted { name, params } => return Dist::from_fit(&name, &params),
+        };
+        dist.validate()?;
+        Ok(dist)
+    }
+}
+
+impl From<Dist> for DistSpec {
+    fn from(dist: Dist) -> Self {
+        match dist {
+            Dist::Normal { mean, std } => DistSpec::Normal { mean, std: Some(std), variance: None },
+            Dist::Exponential { beta } => DistSpec::Exponential { beta },
+            Dist::Uniform { min, max } => DistSpec::Uniform { min, max },
+            Dist::Lognormal { mu, sigma } => DistSpec::Lognormal { mu, sigma },
+            Dist::Gamma { shape, scale } => DistSpec::Gamma { shape, scale },
+            Dist::Beta { alpha, beta } => DistSpec::Beta { alpha, beta },
+            Dist::Weibull { shape, scale } => DistSpec::Weibull { shape, scale },
+            Dist::Triangular { min, mode, max } => DistSpec::Triangular { min, mode, max },
+            Dist::Pert { min, mode, max } => DistSpec::Pert { min, mode, max },
+            Dist::Cauchy { location, scale } => DistSpec::Cauchy { location, scale },
+            Dist::StudentT { df } => DistSpec::StudentT { df },
+            Dist::ChiSquare { df } => DistSpec::ChiSquare { df },
+            Dist::F { d1, d2 } => DistSpec::F { d1, d2 },
+            Dist::Logistic { location, scale } => DistSpec::Logistic { location, scale },
+            Dist::Gumbel { location, scale } => DistSpec::Gumbel { location, scale },
+            Dist::Pareto { scale, shape } => DistSpec::Pareto { scale, shape },
+            Dist::ScaledBeta { alpha, beta, min, max } => DistSpec::ScaledBeta { alpha, beta, min, max },
+            Dist::Poisson { lambda } => DistSpec::Poisson { lambda },
+            Dist::Binomial { n, p } => DistSpec::Binomial { n, p },
+            Dist::Geometric { p } => DistSpec::Geometric { p },
+            Dist::Bernoulli { p } => DistSpec::Bernoulli { p },
+            Dist::NegativeBinomial { r, p } => DistSpec::NegativeBinomial { r, p },
+            Dist::Hypergeometric { population, successes, draws } => {
+                DistSpec::Hypergeometric { population, successes, draws }
+            },
+            Dist::Discrete { values, probabilities } => DistSpec::Discrete { values, probabilities },
+        }
+    }
+}
+
+/// Distribución de un request JSON: `distribution` con el esquema de `DistSpec` o, por
+/// compatibilidad, la forma antigua `dist_type` + `param1..param3` de `Dist::from_params`.
+#[derive(Deserialize)]
+pub struct DistInput {
+    #[serde(default)]
+    pub distribution: Option<Dist>,
+    #[serde(default)]
+    pub dist_type: Option<String>,
+    #[serde(default)]
+    pub param1: f64,
+    #[serde(default)]
+    pub param2: f64,
+    #[serde(default)]
+    pub param3: f64,
+}
+
+impl DistInput {
+    pub fn resolve(&self) -> Result<Dist, String> {
+        match (&self.distribution, &self.dist_type) {
+            (Some(_), Some(_)) => Err("Indique distribution o dist_type, no ambos".to_string()),
+            (Some(dist), None) => Ok(dist.clone()),
+            (None, Some(name)) => Dist::from_params(name, self.param1, self.param2, self.param3),
+            (None, None) => Err("Se requiere distribution (o dist_type y sus parámetros)".to_string()),
+        }
+    }
+}
+
+impl Distribution for Dist {
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            Dist::Normal { mean, std } => {
+                if !mean.is_finite() { return Err("La media debe ser finita".to_string()); }
+                if !(std >= 0.0 && std.is_finite()) { return Err("La desviación estándar debe ser >= 0".to_string()); }
+                Ok(())
+            },
+            Dist::Exponential { beta } => positive(beta, "Beta"),
+            Dist::Uniform { min, max } => {
+                if !(min < max && min.is_finite() && max.is_finite()) { return Err("Min >= Max".to_string()); }
+                Ok(())
+            },
+            Dist::Lognormal { mu, sigma } => {
+                if !mu.is_finite() { return Err("Mu debe ser finito".to_string()); }
+                positive(sigma, "Sigma")
+            },
+            Dist::Gamma { shape, scale } | Dist::Weibull { shape, scale } => {
+                positive(shape, "Forma")?;
+                positive(scale, "Escala")
+            },
+            Dist::Beta { alpha, beta } => {
+                positive(alpha, "Alfa")?;
+                positive(beta, "Beta")
+            },
+            Dist::Triangular { min, mode, max } | Dist::Pert { min, mode, max } => check_mode_range(min, mode, max),
+            Dist::Cauchy { location, scale } | Dist::Logistic { location, scale } | Dist::Gumbel { location, scale } => {
+                if !location.is_finite() { return Err("La ubicación debe ser finita".to_string()); }
+                positive(scale, "Escala")
+            },
+            Dist::Pareto { scale, shape } => {
+                positive(scale, "Escala")?;
+                positive(shape, "Forma")
+            },
+            Dist::ScaledBeta { alpha, beta, min, max } => {
+                positive(alpha, "Alfa")?;
+                positive(beta, "Beta")?;
+                if !(min < max && min.is_finite() && max.is_finite()) { return Err("Min >= Max".to_string()); }
+                Ok(())
+            },
+            Dist::StudentT { df } | Dist::ChiSquare { df } => positive(df, "Grados de libertad"),
+            Dist::F { d1, d2 } => {
+                positive(d1, "d1")?;
+                positive(d2, "d2")
+            },
+            Dist::Poisson { lambda } => positive(lambda, "Lambda"),
+            Dist::Binomial { n, p } => {
+                if n == 0 { return Err("n debe ser >= 1".to_string()); }
+                check_probability(p)
+            },
+            Dist::Geometric { p } => check_open_probability(p),
+            Dist::Bernoulli { p } => check_probability(p),
+            Dist::NegativeBinomial { r, p } => {
+                positive(r, "r")?;
+                check_open_probability(p)
+            },
+            Dist::Hypergeometric { population, successes, draws } => {
+                if successes > population || draws > population {
+                    return Err("Éxitos y extracciones no pueden superar la población".to_string());
+                }
+                Ok(())
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                if values.is_empty() { return Err("La tabla discreta está vacía".to_string()); }
+                if values.len() != probabilities.len() {
+                    return Err(format!("La tabla tiene {} valores y {} probabilidades", values.len(), probabilities.len()));
+                }
+                if let Some(p) = probabilities.iter().find(|p| !(**p >= 0.0 && p.is_finite())) {
+                    return Err(format!("Probabilidad inválida {} en la tabla", p));
+                }
+                let total: f64 = probabilities.iter().sum();
+                if (total - 1.0).abs() > 1e-6 {
+                    return Err(format!("Las probabilidades suman {} (deben sumar 1)", total));
+                }
+                Ok(())
+            },
+        }
+    }
+
+    fn is_discrete(&self) -> bool {
+        matches!(
+            self,
+            Dist::Poisson { .. } | Dist::Binomial { .. } | Dist::Geometric { .. } | Dist::Bernoulli { .. }
+                | Dist::NegativeBinomial { .. } | Dist::Hypergeometric { .. } | Dist::Discrete { .. }
+        )
+    }
+
+    fn support(&self) -> (f64, f64) {
+        match *self {
+            Dist::Normal { .. } | Dist::StudentT { .. } | Dist::Cauchy { .. } | Dist::Logistic { .. } | Dist::Gumbel { .. } => {
+                (f64::NEG_INFINITY, f64::INFINITY)
+            },
+            Dist::Uniform { min, max } | Dist::Triangular { min, max, .. } | Dist::Pert { min, max, .. }
+                | Dist::ScaledBeta { min, max, .. } => (min, max),
+            Dist::Pareto { scale, .. } => (scale, f64::INFINITY),
+            Dist::Beta { .. } | Dist::Bernoulli { .. } => (0.0, 1.0),
+            Dist::Binomial { n, .. } => (0.0, n as f64),
+            Dist::Hypergeometric { population, successes, draws } => (
+                (draws + successes).saturating_sub(population) as f64,
+                draws.min(successes) as f64,
+            ),
+            Dist::Discrete { ref values, .. } => values.iter().fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(lo, hi), &v| (lo.min(v), hi.max(v)),
+            ),
+            _ => (0.0, f64::INFINITY),
+        }
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        let (lo, hi) = self.support();
+        if x < lo { return 0.0; }
+        if x >= hi { return 1.0; }
+        match *self {
+            Dist::Normal { mean, std } => {
+                if std == 0.0 { return 1.0; } // x >= mean (soporte degenerado)
+                standard_normal_cdf((x - mean) / std)
+            },
+            Dist::Exponential { beta } => -(-x / beta).exp_m1(),
+            Dist::Uniform { min, max } => (x - min) / (max - min),
+            Dist::Lognormal { mu, sigma } => if x <= 0.0 { 0.0 } else { standard_normal_cdf((x.ln() - mu) / sigma) },
+            Dist::Gamma { shape, scale } => gamma_p(shape, x / scale),
+            Dist::ChiSquare { df } => gamma_p(df / 2.0, x / 2.0),
+            Dist::Beta { alpha, beta } => beta_inc(alpha, beta, x),
+            Dist::Weibull { shape, scale } => -(-(x / scale).powf(shape)).exp_m1(),
+            Dist::Triangular { min, mode, max } => {
+                // x = min con mode = min daría 0/0
+                if x <= min {
+                    0.0
+                } else if x <= mode {
+                    (x - min).powi(2) / ((max - min) * (mode - min))
+                } else {
+                    1.0 - (max - x).powi(2) / ((max - min) * (max - mode))
+                }
+            },
+            Dist::Pert { min, mode, max } => {
+                let (a, b) = Dist::pert_shape(min, mode, max);
+                beta_inc(a, b, (x - min) / (max - min))
+            },
+            Dist::Cauchy { location, scale } => 0.5 + ((x - location) / scale).atan() / PI,
+            Dist::StudentT { df } => {
+                let tail = 0.5 * beta_inc(df / 2.0, 0.5, df / (df + x * x));
+                if x >= 0.0 { 1.0 - tail } else { tail }
+            },
+            Dist::F { d1, d2 } => beta_inc(d1 / 2.0, d2 / 2.0, d1 * x / (d1 * x + d2)),
+            Dist::Logistic { location, scale } => 1.0 / (1.0 + (-(x - location) / scale).exp()),
+            Dist::Gumbel { location, scale } => (-(-(x - location) / scale).exp()).exp(),
+            Dist::Pareto { scale, shape } => 1.0 - (scale / x).powf(shape),
+            Dist::ScaledBeta { alpha, beta, min, max } => beta_inc(alpha, beta, (x - min) / (max - min)),
+            // P(X <= k) = Q(k + 1, lambda)
+            Dist::Poisson { lambda } => gamma_q(x.floor() + 1.0, lambda),
+            // P(X <= k) = I_{1-p}(n - k, k + 1)
+            Dist::Binomial { n, p } => {
+                let k = x.floor();
+                beta_inc(n as f64 - k, k + 1.0, 1.0 - p)
+            },
+            Dist::Geometric { p } => 1.0 - (1.0 - p).powf(x.floor() + 1.0),
+            Dist::Bernoulli { p } => 1.0 - p,
+            // P(X <= k) = I_p(r, k + 1)
+            Dist::NegativeBinomial { r, p } => beta_inc(r, x.floor() + 1.0, p),
+            Dist::Hypergeometric { .. } => {
+                let k_lo = lo as u64;
+                (k_lo..=x.floor() as u64).map(|k| self.pdf(k as f64)).sum::<f64>().min(1.0)
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                values.iter().zip(probabilities).filter(|(v, _)| **v <= x).map(|(_, p)| p).sum::<f64>().min(1.0)
+            },
+        }
+    }
+
+    fn pdf(&self, x: f64) -> f64 {
+        match *self {
+            Dist::Uniform { min, max } => if x < min || x > max { 0.0 } else { 1.0 / (max - min) },
+            Dist::Triangular { min, mode, max } => {
+                if x < min || x > max {
+                    0.0
+                } else if x < mode {
+                    2.0 * (x - min) / ((max - min) * (mode - min))
+                } else if x == mode {
+                    2.0 / (max - min)
+                } else {
+                    2.0 * (max - x) / ((max - min) * (max - mode))
+                }
+            },
+            Dist::Cauchy { location, scale } => {
+                let z = (x - location) / scale;
+                1.0 / (PI * scale * (1.0 + z * z))
+            },
+            Dist::Bernoulli { p } => {
+                if x == 1.0 { p } else if x == 0.0 { 1.0 - p } else { 0.0 }
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                values.iter().zip(probabilities).filter(|(v, _)| **v == x).map(|(_, p)| p).sum()
+            },
+            // El resto se evalúa en escala logarítmica
+            _ => self.ln_pdf(x).exp(),
+        }
+    }
+
+    fn ln_pdf(&self, x: f64) -> f64 {
+        let (lo, hi) = self.support();
+        if x < lo || x > hi { return f64::NEG_INFINITY; }
+        if self.is_discrete() && x.fract() != 0.0 && !matches!(self, Dist::Discrete { .. }) {
+            return f64::NEG_INFINITY;
+        }
+        match *self {
+            Dist::Normal { mean, std } => {
+                if std == 0.0 { return if x == mean { f64::INFINITY } else { f64::NEG_INFINITY }; }
+                let z = (x - mean) / std;
+                -0.5 * z * z - std.ln() - LN_SQRT_2PI
+            },
+            Dist::Exponential { beta } => -x / beta - beta.ln(),
+            Dist::Lognormal { mu, sigma } => {
+                if x <= 0.0 { return f64::NEG_INFINITY; }
+                let z = (x.ln() - mu) / sigma;
+                -0.5 * z * z - (x * sigma).ln() - LN_SQRT_2PI
+            },
+            Dist::Gamma { shape, scale } => ln_gamma_pdf(x, shape, scale),
+            Dist::ChiSquare { df } => ln_gamma_pdf(x, df / 2.0, 2.0),
+            Dist::Beta { alpha, beta } => ln_beta_pdf(x, alpha, beta),
+            Dist::Pert { min, mode, max } => {
+                let (a, b) = Dist::pert_shape(min, mode, max);
+                ln_beta_pdf((x - min) / (max - min), a, b) - (max - min).ln()
+            },
+            Dist::ScaledBeta { alpha, beta, min, max } => {
+                ln_beta_pdf((x - min) / (max - min), alpha, beta) - (max - min).ln()
+            },
+            Dist::Weibull { shape, scale } => {
+                let z = x / scale;
+                (shape / scale).ln() + xlny(shape - 1.0, z) - z.powf(shape)
+            },
+            // f = e^-z / (s (1 + e^-z)²), simétrica en z
+            Dist::Logistic { location, scale } => {
+                let z = ((x - location) / scale).abs();
+                -z - scale.ln() - 2.0 * (-z).exp().ln_1p()
+            },
+            Dist::Gumbel { location, scale } => {
+                let z = (x - location) / scale;
+                -z - (-z).exp() - scale.ln()
+            },
+            Dist::Pareto { scale, shape } => shape.ln() + shape * scale.ln() - (shape + 1.0) * x.ln(),
+            Dist::StudentT { df } => {
+                let ln_c = ln_gamma((df + 1.0) / 2.0) - ln_gamma(df / 2.0) - 0.5 * (df * PI).ln();
+                ln_c - (df + 1.0) / 2.0 * (1.0 + x * x / df).ln()
+            },
+            Dist::F { d1, d2 } => {
+                if x == 0.0 {
+                    return if d1 < 2.0 { f64::INFINITY } else if d1 == 2.0 { 0.0 } else { f64::NEG_INFINITY };
+                }
+                0.5 * (d1 * (d1 * x).ln() + d2 * d2.ln() - (d1 + d2) * (d1 * x + d2).ln())
+                    - x.ln() - ln_beta(d1 / 2.0, d2 / 2.0)
+            },
+            Dist::Poisson { lambda } => x * lambda.ln() - lambda - ln_gamma(x + 1.0),
+            Dist::Binomial { n, p } => {
+                let n = n as f64;
+                ln_choose(n, x) + xlny(x, p) + xlny(n - x, 1.0 - p)
+            },
+            Dist::Geometric { p } => p.ln() + xlny(x, 1.0 - p),
+            Dist::NegativeBinomial { r, p } => {
+                ln_gamma(x + r) - ln_gamma(r) - ln_gamma(x + 1.0) + r * p.ln() + xlny(x, 1.0 - p)
+            },
+            Dist::Hypergeometric { population, successes, draws } => {
+                let (n_pop, k_succ, n_draw) = (population as f64, successes as f64, draws as f64);
+                ln_choose(k_succ, x) + ln_choose(n_pop - k_succ, n_draw - x) - ln_choose(n_pop, n_draw)
+            },
+            _ => self.pdf(x).ln(),
+        }
+    }
+
+    fn quantile(&self, p: f64) -> f64 {
+        match *self {
+            // X = mu + Z * sigma
+            Dist::Normal { mean, std } => mean + std * standard_normal_quantile(p),
+            // F(x) = 1 - e^(-x/beta)  =>  x = -beta * ln(1 - p)
+            Dist::Exponential { beta } => -beta * (-p).ln_1p(),
+            Dist::Uniform { min, max } => min + (max - min) * p,
+            Dist::Lognormal { mu, sigma } => (mu + sigma * standard_normal_quantile(p)).exp(),
+            Dist::Weibull { shape, scale } => scale * (-(-p).ln_1p()).powf(1.0 / shape),
+            Dist::Triangular { min, mode, max } => {
+                let fc = (mode - min) / (max - min);
+                if p < fc {
+                    min + (p * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - p) * (max - min) * (max - mode)).sqrt()
+                }
+            },
+            Dist::Pert { min, mode, max } => {
+                let (a, b) = Dist::pert_shape(min, mode, max);
+                min + (max - min) * beta_inc_inv(a, b, p)
+            },
+            Dist::Cauchy { location, scale } => location + scale * (PI * (p - 0.5)).tan(),
+            Dist::Logistic { location, scale } => location + scale * (p / (1.0 - p)).ln(),
+            Dist::Gumbel { location, scale } => location - scale * (-p.ln()).ln(),
+            Dist::Pareto { scale, shape } => scale * (-(-p).ln_1p() / shape).exp(),
+            Dist::ScaledBeta { alpha, beta, min, max } => min + (max - min) * beta_inc_inv(alpha, beta, p),
+            Dist::Gamma { shape, scale } => scale * gamma_p_inv(shape, p),
+            Dist::ChiSquare { df } => 2.0 * gamma_p_inv(df / 2.0, p),
+            Dist::Beta { alpha, beta } => beta_inc_inv(alpha, beta, p),
+            // F = (d2 / d1) · y / (1 - y), con y ~ Beta(d1/2, d2/2)
+            Dist::F { d1, d2 } => {
+                let y = beta_inc_inv(d1 / 2.0, d2 / 2.0, p);
+                d2 * y / (d1 * (1.0 - y))
+            },
+            // P(|T| > t) = I_{df/(df+t²)}(df/2, 1/2); simétrica respecto de 0
+            Dist::StudentT { df } => {
+                if p == 0.5 { return 0.0; }
+                let y = beta_inc_inv(df / 2.0, 0.5, 2.0 * p.min(1.0 - p));
+                let t = (df * (1.0 - y) / y).sqrt();
+                if p > 0.5 { t } else { -t }
+            },
+            Dist::Poisson { lambda } => discrete_quantile(|k| self.cdf(k as f64), p, lambda.ceil() as u64 + 1, None),
+            Dist::Binomial { n, .. } => discrete_quantile(|k| self.cdf(k as f64), p, n, Some(n)),
+            // 1 - (1-q)^(k+1) >= p  <=>  k >= ln(1-p) / ln(1-q) - 1
+            Dist::Geometric { p: q } => {
+                if q >= 1.0 { return 0.0; }
+                ((-p).ln_1p() / (-q).ln_1p() - 1.0).ceil().max(0.0)
+            },
+            Dist::Bernoulli { p: q } => if p <= 1.0 - q { 0.0 } else { 1.0 },
+            Dist::NegativeBinomial { r, p: q } => {
+                if q >= 1.0 { return 0.0; }
+                discrete_quantile(|k| self.cdf(k as f64), p, (r * (1.0 - q) / q).ceil() as u64 + 1, None)
+            },
+            Dist::Hypergeometric { .. } => {
+                let (lo, hi) = self.support();
+                let mut acc = 0.0;
+                for k in lo as u64..hi as u64 {
+                    acc += self.pdf(k as f64);
+                    if acc >= p * (1.0 - 1e-12) { return k as f64; }
+                }
+                hi
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                // Primer valor del soporte (ordenado) cuya probabilidad acumulada alcanza p
+                let mut acc = 0.0;
+                for (v, prob) in values.iter().zip(probabilities) {
+                    acc += prob;
+                    if p <= acc { return *v; }
+                }
+                *values.last().unwrap()
+            },
+        }
+    }
+
+    fn moments(&self) -> Moments {
+        match *self {
+            Dist::Normal { mean, std } => Moments::new(mean, std * std, 0.0, 0.0),
+            Dist::Exponential { beta } => Moments::new(beta, beta * beta, 2.0, 6.0),
+            Dist::Uniform { min, max } => Moments::new((min + max) / 2.0, (max - min).powi(2) / 12.0, 0.0, -1.2),
+            Dist::Lognormal { mu, sigma } => {
+                let w = (sigma * sigma).exp();
+                Moments::new(
+                    (mu + sigma * sigma / 2.0).exp(),
+                    (w - 1.0) * (2.0 * mu + sigma * sigma).exp(),
+                    (w + 2.0) * (w - 1.0).sqrt(),
+                    w.powi(4) + 2.0 * w.powi(3) + 3.0 * w.powi(2) - 6.0,
+                )
+            },
+            Dist::Gamma { shape, scale } => Moments::new(shape * scale, shape * scale * scale, 2.0 / shape.sqrt(), 6.0 / shape),
+            Dist::ChiSquare { df } => Moments::new(df, 2.0 * df, (8.0 / df).sqrt(), 12.0 / df),
+            Dist::Beta { alpha, beta } => beta_moments(alpha, beta),
+            Dist::Weibull { shape, scale } => {
+                // g_i = Γ(1 + i/k)
+                let g = |i: f64| ln_gamma(1.0 + i / shape).exp();
+                let (g1, g2, g3, g4) = (g(1.0), g(2.0), g(3.0), g(4.0));
+                let v = g2 - g1 * g1;
+                Moments::new(
+                    scale * g1,
+                    scale * scale * v,
+                    (g3 - 3.0 * g1 * g2 + 2.0 * g1.powi(3)) / v.powf(1.5),
+                    (g4 - 4.0 * g1 * g3 + 6.0 * g1 * g1 * g2 - 3.0 * g1.powi(4)) / (v * v) - 3.0,
+                )
+            },
+            Dist::Triangular { min: a, mode: c, max: b } => {
+                let q = a * a + b * b + c * c - a * b - a * c - b * c;
+                Moments::new(
+                    (a + b + c) / 3.0,
+                    q / 18.0,
+                    SQRT_2 * (a + b - 2.0 * c) * (2.0 * a - b - c) * (a - 2.0 * b + c) / (5.0 * q.powf(1.5)),
+                    -0.6,
+                )
+            },
+            Dist::Pert { min, mode, max } => {
+                let (a, b) = Dist::pert_shape(min, mode, max);
+                let m = beta_moments(a, b);
+                let range = max - min;
+                Moments::new(min + range * m.mean, range * range * m.variance, m.skewness, m.kurtosis_excess)
+            },
+            Dist::ScaledBeta { alpha, beta, min, max } => {
+                let m = beta_moments(alpha, beta);
+                let range = max - min;
+                Moments::new(min + range * m.mean, range * range * m.variance, m.skewness, m.kurtosis_excess)
+            },
+            Dist::Cauchy { .. } => Moments::new(f64::NAN, f64::NAN, f64::NAN, f64::NAN),
+            Dist::Logistic { location, scale } => Moments::new(location, (PI * scale).powi(2) / 3.0, 0.0, 1.2),
+            Dist::Gumbel { location, scale } => Moments::new(
+                location + EULER_GAMMA * scale,
+                (PI * scale).powi(2) / 6.0,
+                GUMBEL_SKEWNESS,
+                2.4,
+            ),
+            Dist::Pareto { scale, shape: a } => Moments::new(
+                if a > 1.0 { a * scale / (a - 1.0) } else { f64::INFINITY },
+                if a > 2.0 { scale * scale * a / ((a - 1.0).powi(2) * (a - 2.0)) } else { f64::INFINITY },
+                if a > 3.0 { 2.0 * (1.0 + a) / (a - 3.0) * ((a - 2.0) / a).sqrt() } else { f64::NAN },
+                if a > 4.0 { 6.0 * (a.powi(3) + a * a - 6.0 * a - 2.0) / (a * (a - 3.0) * (a - 4.0)) } else { f64::NAN },
+            ),
+            Dist::StudentT { df } => Moments::new(
+                if df > 1.0 { 0.0 } else { f64::NAN },
+                if df > 2.0 { df / (df - 2.0) } else if df > 1.0 { f64::INFINITY } else { f64::NAN },
+                if df > 3.0 { 0.0 } else { f64::NAN },
+                if df > 4.0 { 6.0 / (df - 4.0) } else if df > 2.0 { f64::INFINITY } else { f64::NAN },
+            ),
+            Dist::F { d1, d2 } => Moments::new(
+                if d2 > 2.0 { d2 / (d2 - 2.0) } else { f64::NAN },
+                if d2 > 4.0 {
+                    2.0 * d2 * d2 * (d1 + d2 - 2.0) / (d1 * (d2 - 2.0).powi(2) * (d2 - 4.0))
+                } else { f64::NAN },
+                if d2 > 6.0 {
+                    (2.0 * d1 + d2 - 2.0) * (8.0 * (d2 - 4.0)).sqrt() / ((d2 - 6.0) * (d1 * (d1 + d2 - 2.0)).sqrt())
+                } else { f64::NAN },
+                if d2 > 8.0 {
+                    12.0 * (d1 * (5.0 * d2 - 22.0) * (d1 + d2 - 2.0) + (d2 - 4.0) * (d2 - 2.0).powi(2))
+                        / (d1 * (d2 - 6.0) * (d2 - 8.0) * (d1 + d2 - 2.0))
+                } else { f64::NAN },
+            ),
+            Dist::Poisson { lambda } => Moments::new(lambda, lambda, 1.0 / lambda.sqrt(), 1.0 / lambda),
+            Dist::Binomial { n, p } => {
+                let v = n as f64 * p * (1.0 - p);
+                Moments::new(n as f64 * p, v, (1.0 - 2.0 * p) / v.sqrt(), (1.0 - 6.0 * p * (1.0 - p)) / v)
+            },
+            Dist::Geometric { p } => {
+                let q = 1.0 - p;
+                Moments::new(q / p, q / (p * p), (2.0 - p) / q.sqrt(), 6.0 + p * p / q)
+            },
+            Dist::Bernoulli { p } => {
+                let v = p * (1.0 - p);
+                Moments::new(p, v, (1.0 - 2.0 * p) / v.sqrt(), (1.0 - 6.0 * v) / v)
+            },
+            Dist::NegativeBinomial { r, p } => {
+                let q = 1.0 - p;
+                Moments::new(r * q / p, r * q / (p * p), (2.0 - p) / (r * q).sqrt(), 6.0 / r + p * p / (r * q))
+            },
+            Dist::Hypergeometric { population, successes, draws } => {
+                let (nn, k, n) = (population as f64, successes as f64, draws as f64);
+                let var = n * k / nn * (nn - k) / nn * (nn - n) / (nn - 1.0);
+                let skew = (nn - 2.0 * k) * (nn - 1.0).sqrt() * (nn - 2.0 * n)
+                    / ((n * k * (nn - k) * (nn - n)).sqrt() * (nn - 2.0));
+                let kurt = ((nn - 1.0) * nn * nn * (nn * (nn + 1.0) - 6.0 * k * (nn - k) - 6.0 * n * (nn - n))
+                    + 6.0 * n * k * (nn - k) * (nn - n) * (5.0 * nn - 6.0))
+                    / (n * k * (nn - k) * (nn - n) * (nn - 2.0) * (nn - 3.0));
+                Moments::new(n * k / nn, var, skew, kurt)
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                let central = |order: i32, mean: f64| -> f64 {
+                    values.iter().zip(probabilities).map(|(v, p)| p * (v - mean).powi(order)).sum()
+                };
+                let mean: f64 = values.iter().zip(probabilities).map(|(v, p)| v * p).sum();
+                let var = central(2, mean);
+                Moments::new(mean, var, central(3, mean) / var.powf(1.5), central(4, mean) / (var * var) - 3.0)
+            },
+        }
+    }
+
+    fn mode(&self) -> Option<f64> {
+        let mode = match *self {
+            Dist::Normal { mean, .. } => mean,
+            Dist::Exponential { .. } | Dist::Geometric { .. } => 0.0,
+            Dist::Uniform { .. } => return None,
+            Dist::Lognormal { mu, sigma } => (mu - sigma * sigma).exp(),
+            Dist::Gamma { shape, scale } => ((shape - 1.0) * scale).max(0.0),
+            Dist::ChiSquare { df } => (df - 2.0).max(0.0),
+            Dist::Beta { alpha, beta } => {
+                if alpha > 1.0 && beta > 1.0 { (alpha - 1.0) / (alpha + beta - 2.0) }
+                else if alpha <= 1.0 && beta > 1.0 { 0.0 }
+                else if alpha > 1.0 && beta <= 1.0 { 1.0 }
+                else { return None; }
+            },
+            Dist::Weibull { shape, scale } => {
+                if shape > 1.0 { scale * ((shape - 1.0) / shape).powf(1.0 / shape) } else { 0.0 }
+            },
+            Dist::Triangular { mode, .. } | Dist::Pert { mode, .. } => mode,
+            Dist::ScaledBeta { alpha, beta, min, max } => {
+                let unit = Dist::Beta { alpha, beta }.mode()?;
+                min + (max - min) * unit
+            },
+            Dist::Cauchy { location, .. } | Dist::Logistic { location, .. } | Dist::Gumbel { location, .. } => location,
+            Dist::Pareto { scale, .. } => scale,
+            Dist::StudentT { .. } => 0.0,
+            Dist::F { d1, d2 } => if d1 > 2.0 { (d1 - 2.0) / d1 * d2 / (d2 + 2.0) } else { 0.0 },
+            // Si lambda es entero, lambda - 1 también es moda
+            Dist::Poisson { lambda } => {
+                if lambda.fract() == 0.0 { lambda - 1.0 } else { lambda.floor() }
+            },
+            Dist::Binomial { n, p } => ((n as f64 + 1.0) * p).floor().min(n as f64),
+            Dist::Bernoulli { p } => if p > 0.5 { 1.0 } else { 0.0 },
+            Dist::NegativeBinomial { r, p } => if r > 1.0 { ((r - 1.0) * (1.0 - p) / p).floor() } else { 0.0 },
+            Dist::Hypergeometric { population, successes, draws } => {
+                (((draws + 1) * (successes + 1)) as f64 / (population + 2) as f64).floor()
+            },
+            Dist::Discrete { ref values, ref probabilities } => {
+                let (v, _) = values.iter().zip(probabilities).fold((values[0], f64::NEG_INFINITY), |best, (v, p)| {
+                    if *p > best.1 { (*v, *p) } else { best }
+                });
+                v
+            },
+        };
+        Some(mode)
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.sampler().sample(rng)
+    }
+}
+
+/// Muestreador con las constantes de rand_distr ya preparadas (sin comparaciones de strings
+/// ni validaciones en el bucle caliente).
+pub enum Sampler {
+    Normal(Normal<f64>),
+    Exponential(Exp<f64>),
+    Uniform(Uniform<f64>),
+    Lognormal(LogNormal<f64>),
+    Gamma(Gamma<f64>),
+    Beta(Beta<f64>),
+    Weibull(Weibull<f64>),
+    Triangular(Triangular<f64>),
+    Pert(Pert<f64>),
+    Cauchy(Cauchy<f64>),
+    StudentT(StudentT<f64>),
+    ChiSquare(ChiSquared<f64>),
+    F(FisherF<f64>),
+    Logistic(f64, f64), // Transformada inversa (rand_distr no la incluye)
+    Gumbel(Gumbel<f64>),
+    Pareto(Pareto<f64>),
+    ScaledBeta(Beta<f64>, f64, f64), // min + rango · Beta
+    Poisson(Poisson<f64>),
+    Binomial(Binomial),
+    Geometric(Geometric),
+    Bernoulli(Bernoulli),
+    NegativeBinomial(Gamma<f64>), // Mezcla Gamma-Poisson
+    Hypergeometric(Hypergeometric),
+    Table(WeightedIndex<f64>, Vec<f64>),
+    Constant(f64),
+}
+
+impl rand_distr::Distribution<f64> for Sampler {
+    #[inline(always)]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match self {
+            Sampler::Normal(d) => d.sample(rng),
+            Sampler::Exponential(d) => d.sample(rng),
+            Sampler::Uniform(d) => d.sample(rng),
+            Sampler::Lognormal(d) => d.sample(rng),
+            Sampler::Gamma(d) => d.sample(rng),
+            Sampler::Beta(d) => d.sample(rng),
+            Sampler::Weibull(d) => d.sample(rng),
+            Sampler::Triangular(d) => d.sample(rng),
+            Sampler::Pert(d) => d.sample(rng),
+            Sampler::Cauchy(d) => d.sample(rng),
+            Sampler::StudentT(d) => d.sample(rng),
+            Sampler::ChiSquare(d) => d.sample(rng),
+            Sampler::F(d) => d.sample(rng),
+            Sampler::Logistic(location, scale) => {
+                let u: f64 = rng.sample(Open01);
+                location + scale * (u / (1.0 - u)).ln()
+            },
+            Sampler::Gumbel(d) => d.sample(rng),
+            Sampler::Pareto(d) => d.sample(rng),
+            Sampler::ScaledBeta(d, min, range) => min + range * d.sample(rng),
+            Sampler::Poisson(d) => d.sample(rng),
+            Sampler::Binomial(d) => d.sample(rng) as f64,
+            Sampler::Geometric(d) => d.sample(rng) as f64,
+            Sampler::Bernoulli(d) => if d.sample(rng) { 1.0 } else { 0.0 },
+            Sampler::NegativeBinomial(d) => {
+                // X | L ~ Poisson(L), L ~ Gamma(r, (1-p)/p)
+                poisson_variate(d.sample(rng), rng)
+            },
+            Sampler::Hypergeometric(d) => d.sample(rng) as f64,
+            Sampler::Table(d, values) => values[d.sample(rng)],
+            Sampler::Constant(v) => *v,
+        }
+    }
+}
+
+/// Poisson(lambda) sin construir un `rand_distr::Poisson` por extracción (lambda cambia en
+/// cada variable de la mezcla): inversión secuencial para lambda < 10 y PTRS de Hörmann
+/// (1993) por encima.
+fn poisson_variate<R: Rng + ?Sized>(lambda: f64, rng: &mut R) -> f64 {
+    if lambda <= 0.0 { return 0.0; }
+    if lambda < 10.0 {
+        let u: f64 = rng.gen();
+        let (mut k, mut pk) = (0.0, (-lambda).exp());
+        let mut cdf = pk;
+        while u > cdf && pk > 0.0 {
+            k += 1.0;
+            pk *= lambda / k;
+            cdf += pk;
+        }
+        return k;
+    }
+    let ln_lambda = lambda.ln();
+    let b = 0.931 + 2.53 * lambda.sqrt();
+    let a = -0.059 + 0.02483 * b;
+    let ln_inv_alpha = (1.1239 + 1.1328 / (b - 3.4)).ln();
+    let v_r = 0.9277 - 3.6224 / (b - 2.0);
+    loop {
+        let u = rng.gen::<f64>() - 0.5;
+        let v: f64 = rng.gen();
+        let us = 0.5 - u.abs();
+        let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+        if us >= 0.07 && v <= v_r { return k; }
+        if k < 0.0 || (us < 0.013 && v > us) { continue; }
+        if v.ln() + ln_inv_alpha - (a / (us * us) + b).ln() <= -lambda + k * ln_lambda - ln_gamma(k + 1.0) {
+            return k;
+        }
+    }
+}
+
+// ln(sqrt(2 pi))
+const LN_SQRT_2PI: f64 = 0.918_938_533_204_672_8;
+// Constante de Euler–Mascheroni y asimetría de la Gumbel, 12 √6 ζ(3) / pi³
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+const GUMBEL_SKEWNESS: f64 = 1.139_547_099_404_648_7;
+
+/// x · ln(y) con 0 · ln(0) = 0 (exponentes nulos en los bordes del soporte).
+fn xlny(x: f64, y: f64) -> f64 {
+    if x == 0.0 { 0.0 } else { x * y.ln() }
+}
+
+fn ln_gamma_pdf(x: f64, shape: f64, scale: f64) -> f64 {
+    xlny(shape - 1.0, x) - x / scale - ln_gamma(shape) - shape * scale.ln()
+}
+
+fn ln_beta_pdf(x: f64, alpha: f64, beta: f64) -> f64 {
+    if !(0.0..=1.0).contains(&x) { return f64::NEG_INFINITY; }
+    xlny(alpha - 1.0, x) + xlny(beta - 1.0, 1.0 - x) - ln_beta(alpha, beta)
+}
+
+fn beta_moments(a: f64, b: f64) -> Moments {
+    let s = a + b;
+    Moments::new(
+        a / s,
+        a * b / (s * s * (s + 1.0)),
+        2.0 * (b - a) * (s + 1.0).sqrt() / ((s + 2.0) * (a * b).sqrt()),
+        6.0 * ((a - b).powi(2) * (s + 1.0) - a * b * (s + 2.0)) / (a * b * (s + 2.0) * (s + 3.0)),
+    )
+}
+
+/// ln C(n, k) vía log-gamma.
+fn ln_choose(n: f64, k: f64) -> f64 {
+    ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0)
+}
+
+/// Menor k con cdf(k) >= p, por búsqueda binaria. `max` acota el soporte (binomial).
+fn discrete_quantile<F: Fn(u64) -> f64>(cdf: F, p: f64, start: u64, max: Option<u64>) -> f64 {
+    // Tolerancia relativa para que p = F(k) exacto devuelva k pese al redondeo
+    let target = p * (1.0 - 1e-12);
+    if cdf(0) >= target { return 0.0; }
+    let mut hi = start.max(1);
+    while cdf(hi) < target {
+        if let Some(m) = max { if hi >= m { hi = m; break; } }
+        hi = hi.saturating_mul(2);
+    }
+    let mut lo = 0u64;
+    // Invariante: cdf(lo) < target <= cdf(hi)
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if cdf(mid) < target { lo = mid; } else { hi = mid; }
+    }
+    hi as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triangular_cdf_with_mode_on_a_bound() {
+        let left = Dist::Triangular { min: 0.0, mode: 0.0, max: 2.0 };
+        assert_eq!(left.cdf(0.0), 0.0);
+        assert_eq!(left.cdf(2.0), 1.0);
+        assert!((left.cdf(1.0) - 0.75).abs() < 1e-15);
+        let right = Dist::Triangular { min: 0.0, mode: 2.0, max: 2.0 };
+        assert_eq!(right.cdf(0.0), 0.0);
+        assert_eq!(right.cdf(2.0), 1.0);
+        assert!((right.cdf(1.0) - 0.25).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,463 @@
+// src/probabilities/fitting.rs
+// Estimadores de máxima verosimilitud por familia. Cada función devuelve los parámetros en
+// el formato de `Dist::from_fit`, o None si la familia no aplica a los datos (soporte
+// incompatible, datos constantes) o el método numérico no converge.
+use crate::probabilities::distribution::{Dist, Distribution};
+use crate::probabilities::special::{digamma, trigamma};
+use std::f64::consts::PI;
+
+const MAX_ITER: usize = 200;
+const TOL: f64 = 1e-12;
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    sum / n as f64
+}
+
+fn all_positive(data: &[f64]) -> bool {
+    data.len() >= 2 && data.iter().all(|&x| x > 0.0 && x.is_finite())
+}
+
+fn std_pop(data: &[f64]) -> f64 {
+    let m = mean(data.iter().copied());
+    mean(data.iter().map(|x| (x - m).powi(2))).sqrt()
+}
+
+/// Gamma [forma, escala]: Newton sobre ln k - ψ(k) = ln(media) - media(ln x).
+pub fn gamma(data: &[f64]) -> Option<Vec<f64>> {
+    if !all_positive(data) { return None; }
+    let m = mean(data.iter().copied());
+    let s = m.ln() - mean(data.iter().map(|x| x.ln()));
+    if s.is_nan() || s <= 0.0 { return None; }
+    let k = gamma_shape(s)?;
+    Some(vec![k, m / k])
+}
+
+/// Raíz de ln k - ψ(k) = s, con la semilla de Minka.
+fn gamma_shape(s: f64) -> Option<f64> {
+    let mut k = (3.0 - s + ((s - 3.0).powi(2) + 24.0 * s).sqrt()) / (12.0 * s);
+    for _ in 0..MAX_ITER {
+        let f = k.ln() - digamma(k) - s;
+        let df = 1.0 / k - trigamma(k);
+        let mut next = k - f / df;
+        if next <= 0.0 { next = k / 2.0; }
+        let done = (next - k).abs() <= TOL * k;
+        k = next;
+        if done { return Some(k); }
+    }
+    None
+}
+
+/// Erlang [k, beta]: la forma entera (vecina de la forma gamma) con mayor verosimilitud;
+/// para k fijo, beta = media / k.
+pub fn erlang(data: &[f64]) -> Option<Vec<f64>> {
+    let shape = gamma(data)?[0];
+    let m = mean(data.iter().copied());
+    [shape.floor().max(1.0), shape.ceil().max(1.0)]
+        .into_iter()
+        .map(|k| (k, Dist::Gamma { shape: k, scale: m / k }.ln_likelihood(data)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(k, _)| vec![k, m / k])
+}
+
+/// Weibull [forma, escala]: Newton sobre la ecuación de perfil de la forma. Los datos se
+/// dividen por su máximo para que x^k no desborde.
+pub fn weibull(data: &[f64]) -> Option<Vec<f64>> {
+    if !all_positive(data) { return None; }
+    let x_max = data.iter().fold(0.0f64, |a, &b| a.max(b));
+    let ys: Vec<f64> = data.iter().map(|x| x / x_max).collect();
+    let ln_ys: Vec<f64> = ys.iter().map(|y| y.ln()).collect();
+    let mean_ln = mean(ln_ys.iter().copied());
+    let sd_ln = std_pop(&ln_ys);
+    if sd_ln <= 0.0 { return None; }
+
+    // Semilla: sd(ln X) = pi / (k √6)
+    let mut k = PI / (sd_ln * 6f64.sqrt());
+    for _ in 0..MAX_ITER {
+        // g(k) = Σ y^k ln y / Σ y^k - 1/k - media(ln y)
+        let (mut b, mut a, mut c) = (0.0, 0.0, 0.0);
+        for (y, ln_y) in ys.iter().zip(&ln_ys) {
+            let yk = y.powf(k);
+            b += yk;
+            a += yk * ln_y;
+            c += yk * ln_y * ln_y;
+        }
+        let g = a / b - 1.0 / k - mean_ln;
+        let dg = (c * b - a * a) / (b * b) + 1.0 / (k * k);
+        let mut next = k - g / dg;
+        if next <= 0.0 { next = k / 2.0; }
+        let done = (next - k).abs() <= TOL * k;
+        k = next;
+        if done {
+            let scale = x_max * mean(ys.iter().map(|y| y.powf(k))).powf(1.0 / k);
+            return Some(vec![k, scale]);
+        }
+    }
+    None
+}
+
+/// Extremos abiertos para las familias acotadas: si algún dato toca un extremo, ese extremo
+/// se aleja rango / (n + 1) para que la densidad no se anule en los datos.
+fn open_bounds(data: &[f64], min: f64, max: f64) -> (f64, f64) {
+    let (lo_data, hi_data) = data.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+    let min = min.min(lo_data);
+    let max = max.max(hi_data);
+    let delta = (max - min) / (data.len() as f64 + 1.0);
+    (if lo_data <= min { min - delta } else { min }, if hi_data >= max { max + delta } else { max })
+}
+
+/// Beta reescalada [alfa, beta, min, max]: Newton en dos variables sobre los datos llevados
+/// a (0, 1), con semilla por momentos.
+pub fn scaled_beta(data: &[f64], min: f64, max: f64) -> Option<Vec<f64>> {
+    if data.len() < 2 || min >= max { return None; }
+    let (lo, hi) = open_bounds(data, min, max);
+    let ts: Vec<f64> = data.iter().map(|x| (x - lo) / (hi - lo)).collect();
+    let ln_t = mean(ts.iter().map(|t| t.ln()));
+    let ln_1t = mean(ts.iter().map(|t| (-t).ln_1p()));
+
+    let m = mean(ts.iter().copied());
+    let v = std_pop(&ts).powi(2);
+    if v <= 0.0 { return None; }
+    let common = m * (1.0 - m) / v - 1.0;
+    let (mut a, mut b) = if common > 0.0 { (m * common, (1.0 - m) * common) } else { (1.0, 1.0) };
+
+    for _ in 0..MAX_ITER {
+        // ψ(a) - ψ(a + b) = media(ln t), ψ(b) - ψ(a + b) = media(ln(1 - t))
+        let (d_ab, t_ab) = (digamma(a + b), trigamma(a + b));
+        let g1 = digamma(a) - d_ab - ln_t;
+        let g2 = digamma(b) - d_ab - ln_1t;
+        let (j11, j22, j12) = (trigamma(a) - t_ab, trigamma(b) - t_ab, -t_ab);
+        let det = j11 * j22 - j12 * j12;
+        let mut da = (j22 * g1 - j12 * g2) / det;
+        let mut db = (j11 * g2 - j12 * g1) / det;
+        // Paso amortiguado para no salir de a, b > 0
+        while a - da <= 0.0 || b - db <= 0.0 {
+            da /= 2.0;
+            db /= 2.0;
+        }
+        a -= da;
+        b -= db;
+        if !(a.is_finite() && b.is_finite()) { return None; }
+        if da.abs() <= TOL * a && db.abs() <= TOL * b {
+            return Some(vec![a, b, lo, hi]);
+        }
+    }
+    None
+}
+
+/// Triangular [min, moda, max] sobre los extremos abiertos: la moda de máxima verosimilitud
+/// es uno de los datos o un extremo (la log-verosimilitud es convexa entre datos
+/// consecutivos), así que basta recorrerlos con sumas acumuladas. `sorted` debe venir ordenado.
+pub fn triangular(sorted: &[f64], min: f64, max: f64) -> Option<Vec<f64>> {
+    if sorted.len() < 2 || min >= max { return None; }
+    let (lo, hi) = open_bounds(sorted, min, max);
+    let ts: Vec<f64> = sorted.iter().map(|x| (x - lo) / (hi - lo)).collect();
+    let n = ts.len();
+
+    // ln L(c) = Σ_{t_i < c} ln(t_i / c) + Σ_{t_i > c} ln((1 - t_i) / (1 - c)) + cte
+    let total_ln_1t: f64 = ts.iter().map(|t| (-t).ln_1p()).sum();
+    let total_ln_t: f64 = ts.iter().map(|t| t.ln()).sum();
+    let mut best = (0.0, total_ln_1t); // Moda en el extremo inferior
+    if total_ln_t > best.1 { best = (1.0, total_ln_t); }
+    let (mut left, mut right) = (0.0, total_ln_1t);
+    for (r, &c) in ts.iter().enumerate() {
+        right -= (-c).ln_1p();
+        let ll = left - r as f64 * c.ln() + right - (n - 1 - r) as f64 * (-c).ln_1p();
+        if ll > best.1 { best = (c, ll); }
+        left += c.ln();
+    }
+    Some(vec![lo, lo + best.0 * (hi - lo), hi])
+}
+
+/// Pareto [x_m, alfa]: forma cerrada x_m = mínimo, alfa = n / Σ ln(x / x_m).
+pub fn pareto(data: &[f64]) -> Option<Vec<f64>> {
+    if !all_positive(data) { return None; }
+    let xm = data.iter().fold(f64::INFINITY, |a, &b| a.min(b));
+    let sum_ln: f64 = data.iter().map(|x| (x / xm).ln()).sum();
+    if sum_ln <= 0.0 { return None; }
+    Some(vec![xm, data.len() as f64 / sum_ln])
+}
+
+/// Logística [ubicación, escala] por Nelder–Mead sobre (ubicación, ln escala).
+pub fn logistic(data: &[f64]) -> Option<Vec<f64>> {
+    let sd = std_pop(data);
+    if data.len() < 2 || sd <= 0.0 || sd.is_nan() { return None; }
+    let start = [mean(data.iter().copied()), (sd * 3f64.sqrt() / PI).ln()];
+    let neg_ll = |p: &[f64]| -Dist::Logistic { location: p[0], scale: p[1].exp() }.ln_likelihood(data);
+    let p = nelder_mead(neg_ll, &start, &[0.1 * sd, 0.1]);
+    Some(vec![p[0], p[1].exp()])
+}
+
+/// Gumbel de máximos [ubicación, escala] por Nelder–Mead sobre (ubicación, ln escala).
+pub fn gumbel(data: &[f64]) -> Option<Vec<f64>> {
+    let sd = std_pop(data);
+    if data.len() < 2 || sd <= 0.0 || sd.is_nan() { return None; }
+    let beta = sd * 6f64.sqrt() / PI;
+    let start = [mean(data.iter().copied()) - EULER_GAMMA * beta, beta.ln()];
+    let neg_ll = |p: &[f64]| -Dist::Gumbel { location: p[0], scale: p[1].exp() }.ln_likelihood(data);
+    let p = nelder_mead(neg_ll, &start, &[0.1 * sd, 0.1]);
+    Some(vec![p[0], p[1].exp()])
+}
+
+// --- Discretas (datos de conteo) ---
+
+/// Límite del rango de valores para tratar los datos como conteos; con rangos mayores las
+/// frecuencias por valor dejan de tener sentido y se ajustan densidades continuas.
+pub const MAX_COUNT_RANGE: f64 = 10_000.0;
+
+/// Enteros no negativos con rango acotado (p. ej. resultados de generate_poisson).
+pub fn is_count_data(data: &[f64]) -> bool {
+    let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+    for &x in data {
+        if !(x >= 0.0 && x.is_finite() && x.fract() == 0.0) { return false; }
+        lo = lo.min(x);
+        hi = hi.max(x);
+    }
+    !data.is_empty() && hi - lo <= MAX_COUNT_RANGE
+}
+
+/// Pares (valor, frecuencia) de datos ordenados.
+pub fn value_counts(sorted: &[f64]) -> Vec<(f64, f64)> {
+    let mut table: Vec<(f64, f64)> = Vec::new();
+    for &x in sorted {
+        match table.last_mut() {
+            Some((v, c)) if *v == x => *c += 1.0,
+            _ => table.push((x, 1.0)),
+        }
+    }
+    table
+}
+
+/// Σ frecuencia · ln P(X = valor) sobre la tabla de frecuencias.
+fn table_ll(dist: &Dist, table: &[(f64, f64)]) -> f64 {
+    table.iter().map(|&(v, c)| c * dist.ln_pdf(v)).sum()
+}
+
+/// Poisson [lambda]: lambda = media.
+pub fn poisson(data: &[f64]) -> Option<Vec<f64>> {
+    let m = mean(data.iter().copied());
+    if m > 0.0 { Some(vec![m]) } else { None }
+}
+
+/// Geométrica [p] (fracasos antes del primer éxito): p = 1 / (1 + media).
+pub fn geometric(data: &[f64]) -> Option<Vec<f64>> {
+    let m = mean(data.iter().copied());
+    if m >= 0.0 { Some(vec![1.0 / (1.0 + m)]) } else { None }
+}
+
+/// Binomial [n, p] con n desconocido: verosimilitud perfil en n (p = media / n) desde el
+/// máximo observado hasta que deja de crecer. Sin subdispersión (varianza >= media) el
+/// MLE de n no es finito (el límite es la Poisson) y la familia no aplica. Con datos
+/// constantes tampoco: el MLE degenerado n = x, p = 1 tiene ln L = 0 y ganaría siempre.
+pub fn binomial(sorted: &[f64]) -> Option<Vec<f64>> {
+    let m = mean(sorted.iter().copied());
+    let var = std_pop(sorted).powi(2);
+    if m <= 0.0 || var >= m || var == 0.0 { return None; }
+    let table = value_counts(sorted);
+    let x_max = table.last()?.0.max(1.0);
+    let profile = |n: f64| table_ll(&Dist::Binomial { n: n as u64, p: m / n }, &table);
+
+    let (mut n, mut ll) = (x_max, profile(x_max));
+    for _ in 0..MAX_ITER * 500 {
+        let next = profile(n + 1.0);
+        if next <= ll { return Some(vec![n, m / n]); }
+        n += 1.0;
+        ll = next;
+    }
+    None
+}
+
+/// Binomial negativa [r, p] (fracasos antes del r-ésimo éxito): p = r / (r + media) y
+/// Newton sobre la ecuación de perfil de r. Requiere sobredispersión (varianza > media).
+pub fn negative_binomial(sorted: &[f64]) -> Option<Vec<f64>> {
+    let m = mean(sorted.iter().copied());
+    let var = std_pop(sorted).powi(2);
+    if m <= 0.0 || var <= m { return None; }
+    let table = value_counts(sorted);
+    let n = sorted.len() as f64;
+
+    // Semilla por momentos: var = m + m² / r
+    let mut r = m * m / (var - m);
+    for _ in 0..MAX_ITER {
+        // f(r) = Σ ψ(x_i + r) - n ψ(r) + n ln(r / (r + m))
+        let f = table.iter().map(|&(v, c)| c * digamma(v + r)).sum::<f64>() - n * digamma(r) + n * (r / (r + m)).ln();
+        let df = table.iter().map(|&(v, c)| c * trigamma(v + r)).sum::<f64>() - n * trigamma(r)
+            + n * (1.0 / r - 1.0 / (r + m));
+        let mut next = r - f / df;
+        if next <= 0.0 || !next.is_finite() { next = r / 2.0; }
+        let done = (next - r).abs() <= TOL * r;
+        r = next;
+        if done { return Some(vec![r, r / (r + m)]); }
+    }
+    None
+}
+
+/// Minimiza `f` con Nelder–Mead (coeficientes estándar) desde `start`, con pasos iniciales
+/// `steps` por coordenada. Se reinicia una vez desde el mejor vértice para no quedar en un
+/// simplex degenerado.
+fn nelder_mead<F: Fn(&[f64]) -> f64>(f: F, start: &[f64], steps: &[f64]) -> Vec<f64> {
+    let dim = start.len();
+    let mut best = start.to_vec();
+    for _ in 0..2 {
+        let mut simplex: Vec<(Vec<f64>, f64)> = (0..=dim)
+            .map(|i| {
+                let mut x = best.clone();
+                if i > 0 { x[i - 1] += steps[i - 1]; }
+                let fx = f(&x);
+                (x, fx)
+            })
+            .collect();
+
+        for _ in 0..MAX_ITER * 10 {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let (f_best, f_worst) = (simplex[0].1, simplex[dim].1);
+            if (f_worst - f_best).abs() <= TOL * (f_best.abs() + TOL) { break; }
+
+            let centroid: Vec<f64> = (0..dim)
+                .map(|j| simplex[..dim].iter().map(|(x, _)| x[j]).sum::<f64>() / dim as f64)
+                .collect();
+            // Punto sobre la recta centroide-peor: t = -1 refleja, -2 expande, ±0.5 contrae
+            let along = |t: f64| -> Vec<f64> {
+                centroid.iter().zip(&simplex[dim].0).map(|(c, w)| c + t * (w - c)).collect()
+            };
+
+            let reflected = along(-1.0);
+            let f_reflected = f(&reflected);
+            if f_reflected < f_best {
+                let expanded = along(-2.0);
+                let f_expanded = f(&expanded);
+                simplex[dim] = if f_expanded < f_reflected { (expanded, f_expanded) } else { (reflected, f_reflected) };
+            } else if f_reflected < simplex[dim - 1].1 {
+                simplex[dim] = (reflected, f_reflected);
+            } else {
+                let contracted = if f_reflected < f_worst { along(-0.5) } else { along(0.5) };
+                let f_contracted = f(&contracted);
+                if f_contracted < f_reflected.min(f_worst) {
+                    simplex[dim] = (contracted, f_contracted);
+                } else {
+                    // Encoger hacia el mejor vértice
+                    let anchor = simplex[0].0.clone();
+                    for vertex in simplex.iter_mut().skip(1) {
+                        for (x, a) in vertex.0.iter_mut().zip(&anchor) { *x = a + 0.5 * (*x - a); }
+                        vertex.1 = f(&vertex.0);
+                    }
+                }
+            }
+        }
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        best = simplex.swap_remove(0).0;
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use rand_distr::Distribution as _;
+
+    const N: usize = 20_000;
+
+    /// Muestra ordenada de `dist` con semilla fija.
+    fn sample(dist: Dist, seed: u64) -> Vec<f64> {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let sampler = dist.sampler();
+        let mut data: Vec<f64> = (0..N).map(|_| sampler.sample(&mut rng)).collect();
+        data.sort_by(f64::total_cmp);
+        data
+    }
+
+    fn assert_params(name: &str, actual: Option<Vec<f64>>, expected: &[f64], rel: f64) {
+        let actual = actual.unwrap_or_else(|| panic!("{name}: sin ajuste"));
+        assert_eq!(actual.len(), expected.len(), "{name}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= rel * e.abs().max(1.0), "{name}: {actual:?}, esperado {expected:?}");
+        }
+    }
+
+    #[test]
+    fn continuous_estimators_recover_known_parameters() {
+        let gamma_data = sample(Dist::Gamma { shape: 2.5, scale: 3.0 }, 1);
+        assert_params("gamma", gamma(&gamma_data), &[2.5, 3.0], 0.05);
+        let erlang_data = sample(Dist::Gamma { shape: 3.0, scale: 2.0 }, 2);
+        assert_params("erlang", erlang(&erlang_data), &[3.0, 2.0], 0.05);
+        let weibull_data = sample(Dist::Weibull { shape: 1.5, scale: 2.0 }, 3);
+        assert_params("weibull", weibull(&weibull_data), &[1.5, 2.0], 0.05);
+        let beta_data = sample(Dist::ScaledBeta { alpha: 2.0, beta: 5.0, min: 0.0, max: 10.0 }, 4);
+        assert_params("beta", scaled_beta(&beta_data, 0.0, 10.0), &[2.0, 5.0, 0.0, 10.0], 0.05);
+        let tri_data = sample(Dist::Triangular { min: 0.0, mode: 3.0, max: 10.0 }, 5);
+        assert_params("triangular", triangular(&tri_data, 0.0, 10.0), &[0.0, 3.0, 10.0], 0.05);
+        let pareto_data = sample(Dist::Pareto { scale: 1.0, shape: 3.0 }, 6);
+        assert_params("pareto", pareto(&pareto_data), &[1.0, 3.0], 0.05);
+        let logistic_data = sample(Dist::Logistic { location: 5.0, scale: 2.0 }, 7);
+        assert_params("logistic", logistic(&logistic_data), &[5.0, 2.0], 0.05);
+        let gumbel_data = sample(Dist::Gumbel { location: -1.0, scale: 0.5 }, 8);
+        assert_params("gumbel", gumbel(&gumbel_data), &[-1.0, 0.5], 0.05);
+    }
+
+    #[test]
+    fn count_estimators_recover_known_parameters() {
+        let poisson_data = sample(Dist::Poisson { lambda: 4.0 }, 11);
+        assert!(is_count_data(&poisson_data));
+        assert_params("poisson", poisson(&poisson_data), &[4.0], 0.05);
+        let geometric_data = sample(Dist::Geometric { p: 0.3 }, 12);
+        assert_params("geometric", geometric(&geometric_data), &[0.3], 0.05);
+        let binomial_data = sample(Dist::Binomial { n: 10, p: 0.3 }, 13);
+        assert_params("binomial", binomial(&binomial_data), &[10.0, 0.3], 0.1);
+        let nb_data = sample(Dist::NegativeBinomial { r: 3.0, p: 0.4 }, 14);
+        assert_params("negative_binomial", negative_binomial(&nb_data), &[3.0, 0.4], 0.1);
+
+        // Sin sub/sobredispersión la binomial y la binomial negativa no aplican
+        assert!(binomial(&nb_data).is_none());
+        assert!(negative_binomial(&binomial_data).is_none());
+        assert!(!is_count_data(&[0.0, 1.5]));
+        assert!(!is_count_data(&[0.0, MAX_COUNT_RANGE + 1.0]));
+        assert_eq!(value_counts(&[1.0, 1.0, 2.0, 4.0, 4.0]), vec![(1.0, 2.0), (2.0, 1.0), (4.0, 2.0)]);
+    }
+
+    #[test]
+    fn constant_data_has_no_fit() {
+        let constant = [3.0; 50];
+        assert!(gamma(&constant).is_none());
+        assert!(erlang(&constant).is_none());
+        assert!(weibull(&constant).is_none());
+        assert!(scaled_beta(&constant, 3.0, 3.0).is_none());
+        assert!(triangular(&constant, 3.0, 3.0).is_none());
+        assert!(pareto(&constant).is_none());
+        assert!(logistic(&constant).is_none());
+        assert!(gumbel(&constant).is_none());
+        assert!(binomial(&constant).is_none());
+        assert!(negative_binomial(&constant).is_none());
+        assert!(gamma(&[3.0]).is_none());
+        assert!(logistic(&[3.0]).is_none());
+    }
+
+    #[test]
+    fn two_point_data_gives_finite_likelihoods() {
+        // Con dos valores distintos cada familia aplicable devuelve parámetros válidos y un
+        // AIC finito para analysis::candidate
+        let data = [1.0, 2.0];
+        let fits = [
+            ("gamma", gamma(&data)),
+            ("erlang", erlang(&data)),
+            ("weibull", weibull(&data)),
+            ("beta", scaled_beta(&data, 1.0, 2.0)),
+            ("triangular", triangular(&data, 1.0, 2.0)),
+            ("pareto", pareto(&data)),
+            ("logistic", logistic(&data)),
+            ("gumbel", gumbel(&data)),
+            ("poisson", poisson(&data)),
+            ("geometric", geometric(&data)),
+            ("binomial", binomial(&data)),
+        ];
+        for (name, params) in fits {
+            let params = params.unwrap_or_else(|| panic!("{name}: sin ajuste"));
+            let ll = Dist::from_fit(name, &params).unwrap().ln_likelihood(&data);
+            assert!(ll.is_finite(), "{name}: {params:?} ln L = {ll}");
+        }
+        // Varianza 0.25 < media 1.5: sin sobredispersión
+        assert!(negative_binomial(&data).is_none());
+    }
+}
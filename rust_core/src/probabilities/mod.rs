@@ -1,2 +1,6 @@
 // src/probabilities/mod.rs
 pub mod cdf_pdf;
+pub mod distribution;
+pub mod calculator;
+pub mod special;
+pub mod fitting;
@@ -1,11 +1,18 @@
 // src/sampling/generator.rs
 use rand::{Rng, SeedableRng}; // Corregido import
 use rand_chacha::ChaCha20Rng;
-use rand_distr::{Distribution, Normal, Exp, Uniform}; // Importar traits necesarios
+use rand_distr::{Distribution as _, Normal, Exp, Uniform}; // Importar traits necesarios
+use serde::{Deserialize, Serialize};
 use std::slice;
 //use crate::sampling::distributions; // Helper antiguo
-use crate::json_helpers::to_cstring;
+use crate::json_helpers::{run_json, to_cstring};
+use crate::simulations::montecarlo::engine::build_sampler;
+use crate::simulations::montecarlo::models::DistType;
 use crate::errors::Error;
+use crate::aggregation::histogram::{calculate_histogram_logic, HistJson};
+use crate::stats::summary::calculate_summary_sorted;
+use crate::aggregation::boxplot::percentile_sorted;
+use crate::probabilities::distribution::{Dist, DistInput, Distribution};
 
 /// Fill a buffer with uniform(0,1)
 pub fn generate_uniform(ptr: *mut f64, len: usize, seed: u64) {
@@ -58,7 +65,7 @@ pub fn generate_poisson(ptr: *mut f64, len: usize, lambda: f64, seed: u64 ) {
         
         let dist = rand_distr::Poisson::new(lambda).unwrap();
         for v in buf.iter_mut() {
-            *v = dist.sample(&mut rng) as f64;
+            *v = dist.sample(&mut rng);
         }
     }
 }
@@ -76,66 +83,319 @@ pub fn generate_binomial(ptr: *mut f64, len: usize, n: u64, p: f64, seed: u64 )
     }
 }
 
-// --- MONTECARLO OPTIMIZADO ---
+/// Petición del generador genérico: cualquier DistType de montecarlo (uniforme(a,b), gamma,
+/// beta, Weibull, Erlang, t de Student, tablas discretas, ...).
+#[derive(Deserialize)]
+pub struct VariateRequest {
+    pub distribution: DistType,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct VariateResponse {
+    pub count: usize,
+    pub seed: u64,
+}
 
-// Enum interno para evitar comparaciones de strings/ids en el bucle caliente
-enum PreparedDist {
-    Uniform(Uniform<f64>),
-    Normal(Normal<f64>),
-    Exponential(Exp<f64>),
-    // Aquí puedes agregar Gamma, Beta, etc. en el futuro
+/// Llena `buf` con variables de la distribución pedida.
+pub fn fill_variates(buf: &mut [f64], req: &VariateRequest) -> Result<VariateResponse, Error> {
+    let sampler = build_sampler("distribution", &req.distribution, 1.0)?;
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    for v in buf.iter_mut() {
+        *v = sampler.sample(&mut rng);
+    }
+    Ok(VariateResponse { count: buf.len(), seed })
 }
 
-impl PreparedDist {
-    #[inline(always)]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+/// Buffer filler descrito por JSON. Devuelve {"count", "seed"} o {"error"}.
+pub fn generate_variates(ptr: *mut f64, len: usize, json_request: *const libc::c_char) -> *mut libc::c_char {
+    if ptr.is_null() || len == 0 {
+        return to_cstring(&serde_json::json!({"error": Error::NullOrEmptyInput.to_string()}));
+    }
+    let buf = unsafe { slice::from_raw_parts_mut(ptr, len) };
+    run_json(json_request, |req: &VariateRequest| fill_variates(buf, req))
+}
+
+// --- MONTECARLO OPTIMIZADO (Distribuciones muestrales / TLC) ---
+
+/// Estadístico calculado sobre cada muestra simulada.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SampleStatistic {
+    #[default]
+    Mean,
+    Median,
+    Variance, // Varianza muestral (n - 1)
+    Proportion { threshold: f64 }, // Proporción de observaciones <= threshold
+    Range,
+    Max,
+}
+
+impl SampleStatistic {
+    fn name(self) -> &'static str {
         match self {
-            PreparedDist::Uniform(d) => d.sample(rng),
-            PreparedDist::Normal(d) => d.sample(rng),
-            PreparedDist::Exponential(d) => d.sample(rng),
+            SampleStatistic::Mean => "mean",
+            SampleStatistic::Median => "median",
+            SampleStatistic::Variance => "variance",
+            SampleStatistic::Proportion { .. } => "proportion",
+            SampleStatistic::Range => "range",
+            SampleStatistic::Max => "max",
+        }
+    }
+
+    /// Calcula el estadístico. `sample` puede quedar reordenada.
+    fn compute(self, sample: &mut [f64]) -> f64 {
+        let n = sample.len() as f64;
+        match self {
+            SampleStatistic::Mean => sample.iter().sum::<f64>() / n,
+            SampleStatistic::Median => {
+                let len = sample.len();
+                let mid = len / 2;
+                let cmp = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
+                let (lower, m, _) = sample.select_nth_unstable_by(mid, cmp);
+                let m = *m;
+                if len.is_multiple_of(2) {
+                    let prev = lower.iter().cloned().fold(f64::MIN, f64::max);
+                    (prev + m) * 0.5
+                } else {
+                    m
+                }
+            },
+            SampleStatistic::Variance => {
+                let mean = sample.iter().sum::<f64>() / n;
+                sample.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+            },
+            SampleStatistic::Proportion { threshold } => {
+                sample.iter().filter(|&&x| x <= threshold).count() as f64 / n
+            },
+            SampleStatistic::Range => {
+                let (lo, hi) = sample.iter().fold((f64::MAX, f64::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+                hi - lo
+            },
+            SampleStatistic::Max => sample.iter().cloned().fold(f64::MIN, f64::max),
         }
     }
 }
 
-pub fn montecarlo_stats_json(
-    dist_id: i32,
-    n_samples: usize,
-    n_trials: usize,
-    param1: f64,
-    param2: f64,
-    seed: u64,
-) -> Result<*mut libc::c_char, Error> {
-    if n_samples == 0 || n_trials == 0 { return Err(Error::NullOrEmptyInput); }
-    
-    // 1. Preparar la distribución UNA SOLA VEZ
-    let dist = match dist_id {
-        0 => PreparedDist::Uniform(Uniform::new(0.0, 1.0)), 
-        // Nota: Uniforme estándar es 0-1, si necesitas parámetros, ajusta aquí.
-        // Si param1/param2 se usan para min/max en uniforme, cámbialo a Uniform::new(param1, param2)
-        
-        1 => PreparedDist::Normal(Normal::new(param1, param2).unwrap()), // param1=mean, param2=std
-        
-        2 => {
-            // param1 = beta (scale). lambda = 1/beta
-            let lambda = if param1.abs() < 1e-9 { 1.0 } else { 1.0 / param1 };
-            PreparedDist::Exponential(Exp::new(lambda).unwrap())
+#[derive(Deserialize)]
+pub struct SamplingDistRequest {
+    // `distribution` ({"type": "normal", "mean": 0, "std": 1}) o la forma antigua
+    // dist_type + param1/param2. Ojo: en la forma antigua la normal recibe la desviación estándar
+    #[serde(flatten)]
+    pub distribution: DistInput,
+    pub sample_size: usize,
+    pub n_trials: usize,
+    #[serde(default)]
+    pub statistic: SampleStatistic,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub bins: Option<usize>, // Por defecto Sturges
+}
+
+#[derive(Serialize)]
+pub struct NormalityTest {
+    pub test: &'static str,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+#[derive(Serialize)]
+pub struct OverlayCurve {
+    pub kind: &'static str, // "normal" o "chi_square"
+    pub x: Vec<f64>,
+    pub density: Vec<f64>,
+    pub freq: Vec<f64>, // Densidad escalada a frecuencia del histograma
+}
+
+#[derive(Serialize)]
+pub struct QuantilePoint {
+    pub p: f64,
+    pub value: f64,
+}
+
+#[derive(Serialize)]
+pub struct SamplingDistResponse {
+    pub statistic: &'static str,
+    pub sample_size: usize,
+    pub n_trials: usize,
+    pub seed: u64,
+    pub estimate_mean: f64,             // Media del estadístico simulado
+    pub estimate_se: f64,               // Error estándar simulado (desv. de las réplicas)
+    pub theoretical_value: Option<f64>, // Parámetro que estima el estadístico (mu, mediana, sigma², p, ...)
+    pub theoretical_se: Option<f64>,    // Ej. sigma / sqrt(n) para la media
+    pub bias: Option<f64>,              // estimate_mean - theoretical_value
+    pub quantiles: Vec<QuantilePoint>,
+    pub normality: NormalityTest,
+    pub histogram: HistJson,
+    pub overlay: Option<OverlayCurve>,
+    pub trial_values: Vec<f64>,
+}
+
+/// Características teóricas de la población muestreada.
+struct Population {
+    mean: f64,
+    std: f64,
+    excess_kurtosis: f64,
+    median: Option<f64>,           // Solo continuas
+    density_at_median: Option<f64>, // Para el SE asintótico de la mediana
+    upper_bound: Option<f64>,      // Soporte acotado (uniforme, binomial, ...)
+    lower_bound: Option<f64>,
+}
+
+/// Valida parámetros y devuelve la distribución y su población teórica.
+fn prepare_dist(input: &DistInput) -> Result<(Dist, Population), Error> {
+    let dist = match (&input.distribution, input.dist_type.as_deref()) {
+        // Forma antigua: normal con (media, desviación) en lugar de (media, varianza)
+        (None, Some("normal")) => {
+            if input.param2 <= 0.0 { return Err(Error::Other("La desviación estándar debe ser > 0".into())); }
+            Dist::Normal { mean: input.param1, std: input.param2 }
         },
-        
-        // Default fallback
-        _ => PreparedDist::Uniform(Uniform::new(0.0, 1.0)),
+        _ => input.resolve().map_err(Error::Other)?,
     };
+    let m = dist.moments();
+    let (lo, hi) = dist.support();
+    let median = if dist.is_discrete() { None } else { Some(dist.median()) };
+    let pop = Population {
+        mean: m.mean,
+        std: m.std(),
+        // Población degenerada (binomial con p = 0 o 1): sin dispersión
+        excess_kurtosis: if m.variance > 0.0 { m.kurtosis_excess } else { 0.0 },
+        median,
+        density_at_median: median.map(|x| dist.pdf(x)),
+        upper_bound: Some(hi).filter(|v| v.is_finite()),
+        lower_bound: Some(lo).filter(|v| v.is_finite()),
+    };
+    Ok((dist, pop))
+}
+
+/// Distribución muestral de un estadístico: `n_trials` muestras de tamaño `sample_size`.
+pub fn sampling_distribution(req: &SamplingDistRequest) -> Result<SamplingDistResponse, Error> {
+    if req.sample_size == 0 || req.n_trials == 0 { return Err(Error::NullOrEmptyInput); }
+    let needs_two = matches!(req.statistic, SampleStatistic::Variance | SampleStatistic::Range);
+    if needs_two && req.sample_size < 2 {
+        return Err(Error::Other(format!("El estadístico '{}' requiere muestras de tamaño >= 2", req.statistic.name())));
+    }
+
+    // 1. Preparar la distribución UNA SOLA VEZ
+    let (dist, pop) = prepare_dist(&req.distribution)?;
+    let sampler = dist.sampler();
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
 
     let mut rng = ChaCha20Rng::seed_from_u64(seed);
-    let mut results = Vec::with_capacity(n_trials);
+    let mut results = Vec::with_capacity(req.n_trials);
+    let mut sample = vec![0.0f64; req.sample_size];
 
-    // 2. Bucle optimizado
-    for _ in 0..n_trials {
-        let mut sum = 0.0f64;
-        for _ in 0..n_samples {
-            sum += dist.sample(&mut rng);
+    // 2. Bucle optimizado (un buffer reutilizado por réplica)
+    for _ in 0..req.n_trials {
+        for v in sample.iter_mut() {
+            *v = sampler.sample(&mut rng);
         }
-        results.push(sum / (n_samples as f64));
+        results.push(req.statistic.compute(&mut sample));
     }
 
-    Ok(to_cstring(&results))
-}
\ No newline at end of file
+    // 3. Resumen del estadístico
+    let mut sorted = results.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let k = req.bins.filter(|&b| b > 0).unwrap_or_else(|| crate::utils::sturges_bins(sorted.len()));
+    let stats = calculate_summary_sorted(&sorted, k);
+    let histogram = calculate_histogram_logic(&sorted, k, stats.min, stats.max);
+    let normality = jarque_bera(stats.n, stats.skewness, stats.kurtosis_excess);
+
+    let quantiles = [0.025, 0.05, 0.25, 0.5, 0.75, 0.95, 0.975].iter()
+        .map(|&p| QuantilePoint { p, value: percentile_sorted(&sorted, p) })
+        .collect();
+
+    // 4. Valores teóricos
+    let n = req.sample_size as f64;
+    let sigma2 = pop.std * pop.std;
+    let (theoretical_value, theoretical_se) = match req.statistic {
+        SampleStatistic::Mean => (Some(pop.mean), Some(pop.std / n.sqrt())),
+        SampleStatistic::Median => (
+            pop.median,
+            pop.density_at_median.map(|f| 1.0 / (2.0 * f * n.sqrt())),
+        ),
+        SampleStatistic::Variance => {
+            // Var(S²) = sigma⁴ (kurtosis/n - (n-3)/(n(n-1))), con kurtosis = exceso + 3
+            let kurt = pop.excess_kurtosis + 3.0;
+            let var_s2 = sigma2 * sigma2 * (kurt / n - (n - 3.0) / (n * (n - 1.0)));
+            (Some(sigma2), Some(var_s2.max(0.0).sqrt()))
+        },
+        SampleStatistic::Proportion { threshold } => {
+            let p = dist.cdf(threshold);
+            (Some(p), Some((p * (1.0 - p) / n).sqrt()))
+        },
+        SampleStatistic::Range => (pop.upper_bound.zip(pop.lower_bound).map(|(hi, lo)| hi - lo), None),
+        SampleStatistic::Max => (pop.upper_bound, None),
+    };
+    let bias = theoretical_value.map(|t| stats.mean - t);
+
+    // 5. Curva teórica superpuesta
+    let overlay = match (req.statistic, theoretical_value, theoretical_se) {
+        (SampleStatistic::Variance, Some(s2), _) if s2 > 0.0 => {
+            // (n-1) S² / sigma² ~ Chi²(n-1) (exacto para poblaciones normales)
+            let df = n - 1.0;
+            let scale = df / s2;
+            let chi2 = Dist::ChiSquare { df };
+            // Con df = 1 la densidad diverge en 0; se grafica desde 0 como 0
+            Some(overlay_curve("chi_square", 0.0, stats.max.max(s2 * 3.0), stats.n, histogram.amplitude,
+                |x| if x > 0.0 { chi2.pdf(x * scale) * scale } else { 0.0 }))
+        },
+        (SampleStatistic::Variance, _, _) => None,
+        (_, Some(mu), Some(se)) if se > 0.0 => {
+            let lo = stats.min.min(mu - 4.0 * se);
+            let hi = stats.max.max(mu + 4.0 * se);
+            let normal = Dist::Normal { mean: mu, std: se };
+            Some(overlay_curve("normal", lo, hi, stats.n, histogram.amplitude, |x| normal.pdf(x)))
+        },
+        _ => None,
+    };
+
+    Ok(SamplingDistResponse {
+        statistic: req.statistic.name(),
+        sample_size: req.sample_size,
+        n_trials: req.n_trials,
+        seed,
+        estimate_mean: stats.mean,
+        estimate_se: stats.std_sample,
+        theoretical_value,
+        theoretical_se,
+        bias,
+        quantiles,
+        normality,
+        histogram,
+        overlay,
+        trial_values: results,
+    })
+}
+
+fn overlay_curve<F: Fn(f64) -> f64>(kind: &'static str, lo: f64, hi: f64, n: usize, width: f64, pdf: F) -> OverlayCurve {
+    let mpoints = 100usize;
+    let mut curve = OverlayCurve {
+        kind,
+        x: Vec::with_capacity(mpoints),
+        density: Vec::with_capacity(mpoints),
+        freq: Vec::with_capacity(mpoints),
+    };
+    for i in 0..mpoints {
+        let x = lo + (hi - lo) * (i as f64) / ((mpoints - 1) as f64);
+        let d = pdf(x);
+        curve.x.push(x);
+        curve.density.push(d);
+        curve.freq.push(d * n as f64 * width);
+    }
+    curve
+}
+
+/// Jarque-Bera: JB = n/6 (S² + K²/4) ~ Chi²(2), cuyo p-valor es exactamente e^(-JB/2).
+fn jarque_bera(n: usize, skewness: f64, kurtosis_excess: f64) -> NormalityTest {
+    let statistic = (n as f64) / 6.0 * (skewness * skewness + kurtosis_excess * kurtosis_excess / 4.0);
+    NormalityTest { test: "jarque_bera", statistic, p_value: (-statistic / 2.0).exp() }
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn montecarlo_stats_json(json_request: *const libc::c_char) -> *mut libc::c_char {
+    run_json(json_request, sampling_distribution)
+}
@@ -0,0 +1,357 @@
+// src/sampling/methods.rs
+// Métodos de generación de variables aleatorias "paso a paso": transformada inversa,
+// aceptación-rechazo, convolución y composición. Cada variable guarda los uniformes que consumió.
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{E, PI};
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::json_helpers::run_json;
+use crate::probabilities::distribution::{Dist, Distribution};
+use crate::simulations::montecarlo::models::DistType;
+
+fn default_count() -> usize {
+    100
+}
+
+fn default_steps_returned() -> usize {
+    50
+}
+
+#[derive(Deserialize)]
+pub struct MixtureComponent {
+    pub weight: f64,
+    pub distribution: DistType,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum GenerationMethod {
+    // Cualquier distribución paramétrica (F^-1 de probabilities::distribution)
+    InverseTransform { distribution: DistType },
+    // Beta, triangular y PERT con envolvente uniforme; normal con envolvente exponencial
+    AcceptanceRejection { distribution: DistType },
+    // Erlang (suma de exponenciales), binomial (suma de Bernoulli), normal (suma de 12 uniformes)
+    // y binomial negativa con r entero (suma de geométricas)
+    Convolution { distribution: DistType },
+    // Mezcla: un uniforme elige la componente y otro genera por transformada inversa
+    Composition { components: Vec<MixtureComponent> },
+}
+
+#[derive(Deserialize)]
+pub struct MethodRequest {
+    #[serde(flatten)]
+    pub method: GenerationMethod,
+    #[serde(default = "default_count")]
+    pub count: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default = "default_steps_returned")]
+    pub steps_returned: usize, // Pasos detallados incluidos en la respuesta
+}
+
+#[derive(Serialize)]
+pub struct GenerationStep {
+    pub value: f64,
+    pub uniforms: Vec<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trials: Option<usize>,       // Aceptación-rechazo: intentos hasta aceptar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<usize>,    // Composición: componente elegida
+}
+
+#[derive(Serialize)]
+pub struct MethodResponse {
+    pub method: &'static str,
+    pub seed: u64,
+    pub values: Vec<f64>,
+    pub steps: Vec<GenerationStep>,
+    pub uniforms_used: usize,
+    pub acceptance_rate: Option<f64>,
+    pub theoretical_acceptance: Option<f64>,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// Fuente de uniformes que registra cada valor consumido.
+struct UniformTape {
+    rng: ChaCha20Rng,
+    used: Vec<f64>,
+}
+
+impl UniformTape {
+    fn next(&mut self) -> f64 {
+        let u: f64 = self.rng.gen();
+        self.used.push(u);
+        u
+    }
+
+    fn take(&mut self) -> Vec<f64> {
+        std::mem::take(&mut self.used)
+    }
+}
+
+/// Distribución a usar con X = F^-1(U): cualquiera salvo los datos empíricos.
+fn invertible(name: &str, dist: &DistType) -> Result<Dist, Error> {
+    match *dist {
+        DistType::Param(ref dist) => Ok(dist.clone()),
+        DistType::Empirical { .. } => {
+            Err(Error::Other(format!("La transformada inversa requiere una distribución paramétrica en {}", name)))
+        },
+    }
+}
+
+/// Objetivo de aceptación-rechazo ya resuelto: densidad acotada en [lo, hi] con su máximo
+/// (envolvente uniforme) o normal (envolvente exponencial).
+enum RejectionTarget {
+    Bounded { lo: f64, hi: f64, max_density: f64, dist: Dist },
+    Normal { mean: f64, std: f64 },
+}
+
+fn rejection_target(dist: &DistType) -> Result<RejectionTarget, Error> {
+    let dist = match invertible("distribution", dist)? {
+        Dist::Normal { mean, std } => return Ok(RejectionTarget::Normal { mean, std }),
+        Dist::Beta { alpha, beta } if alpha < 1.0 || beta < 1.0 => {
+            return Err(Error::Other("Aceptación-rechazo con envolvente uniforme requiere alfa >= 1 y beta >= 1".into()));
+        },
+        d @ (Dist::Beta { .. } | Dist::Pert { .. } | Dist::Triangular { .. }) => d,
+        _ => return Err(Error::Other("Aceptación-rechazo disponible para beta, triangular, PERT y normal".into())),
+    };
+    // Con alfa, beta >= 1 la densidad es acotada y su máximo está en la moda (sin moda: uniforme)
+    let (lo, hi) = dist.support();
+    let max_density = dist.pdf(dist.mode().unwrap_or(lo));
+    Ok(RejectionTarget::Bounded { lo, hi, max_density, dist })
+}
+
+fn sample_mean_var(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = if n > 1.0 { values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0) } else { 0.0 };
+    (mean, var)
+}
+
+pub fn generate_with_method(req: &MethodRequest) -> Result<MethodResponse, Error> {
+    if req.count == 0 { return Err(Error::NullOrEmptyInput); }
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut tape = UniformTape { rng: ChaCha20Rng::seed_from_u64(seed), used: Vec::new() };
+    let mut values = Vec::with_capacity(req.count);
+    let mut steps = Vec::new();
+    let mut uniforms_used = 0usize;
+    let mut total_trials = 0usize;
+    let mut theoretical_acceptance = None;
+
+    // Registra un valor generado y, si cabe, su detalle
+    let mut record = |value: f64, uniforms: Vec<f64>, trials: Option<usize>, component: Option<usize>, steps: &mut Vec<GenerationStep>| {
+        uniforms_used += uniforms.len();
+        values.push(value);
+        if steps.len() < req.steps_returned {
+            steps.push(GenerationStep { value, uniforms, trials, component });
+        }
+    };
+
+    let method = match &req.method {
+        GenerationMethod::InverseTransform { distribution } => {
+            let dist = invertible("distribution", distribution)?;
+            for _ in 0..req.count {
+                let u = tape.next();
+                let x = dist.quantile(u);
+                record(x, tape.take(), None, None, &mut steps);
+            }
+            "inverse_transform"
+        },
+        GenerationMethod::AcceptanceRejection { distribution } => {
+            match rejection_target(distribution)? {
+                RejectionTarget::Bounded { lo, hi, max_density, dist } => {
+                    // Envolvente c·g con g = U(lo, hi) y c = M (hi - lo)
+                    let c = max_density * (hi - lo);
+                    theoretical_acceptance = Some(1.0 / c);
+                    for _ in 0..req.count {
+                        let mut trials = 0;
+                        let x = loop {
+                            trials += 1;
+                            let y = lo + (hi - lo) * tape.next();
+                            if tape.next() * max_density <= dist.pdf(y) { break y; }
+                        };
+                        total_trials += trials;
+                        record(x, tape.take(), Some(trials), None, &mut steps);
+                    }
+                },
+                RejectionTarget::Normal { mean, std } => {
+                    // |Z| con envolvente Exp(1), c = sqrt(2e/pi); un tercer uniforme da el signo
+                    theoretical_acceptance = Some((PI / (2.0 * E)).sqrt());
+                    for _ in 0..req.count {
+                        let mut trials = 0;
+                        let z = loop {
+                            trials += 1;
+                            let y = -(1.0 - tape.next()).ln();
+                            if tape.next() <= (-(y - 1.0).powi(2) / 2.0).exp() { break y; }
+                        };
+                        let z = if tape.next() < 0.5 { -z } else { z };
+                        total_trials += trials;
+                        record(mean + std * z, tape.take(), Some(trials), None, &mut steps);
+                    }
+                },
+            }
+            "acceptance_rejection"
+        },
+        GenerationMethod::Convolution { distribution } => {
+            let dist = invertible("distribution", distribution)?;
+            for _ in 0..req.count {
+                let x = match dist {
+                    // Erlang(k, beta) = Gamma con forma entera
+                    Dist::Gamma { shape, scale } if shape.fract() == 0.0 => {
+                        (0..shape as u64).map(|_| -scale * (1.0 - tape.next()).ln()).sum()
+                    },
+                    Dist::Binomial { n, p } => (0..n).filter(|_| tape.next() < p).count() as f64,
+                    Dist::Normal { mean, std } => {
+                        // Suma de 12 uniformes: media 6 y varianza 1
+                        let z = (0..12).map(|_| tape.next()).sum::<f64>() - 6.0;
+                        mean + std * z
+                    },
+                    Dist::NegativeBinomial { r, p } => {
+                        if r.fract() != 0.0 {
+                            return Err(Error::Other("Convolución de binomial negativa requiere r entero".into()));
+                        }
+                        let geometric = Dist::Geometric { p };
+                        (0..r as u64).map(|_| geometric.quantile(tape.next())).sum()
+                    },
+                    _ => return Err(Error::Other("Convolución disponible para Erlang, binomial, normal y binomial negativa".into())),
+                };
+                record(x, tape.take(), None, None, &mut steps);
+            }
+            "convolution"
+        },
+        GenerationMethod::Composition { components } => {
+            if components.is_empty() { return Err(Error::NullOrEmptyInput); }
+            if components.iter().any(|c| !(c.weight >= 0.0 && c.weight.is_finite())) {
+                return Err(Error::Other("Los pesos deben ser >= 0".into()));
+            }
+            let total: f64 = components.iter().map(|c| c.weight).sum();
+            if (total - 1.0).abs() > 1e-6 {
+                return Err(Error::Other(format!("Los pesos suman {} (deben sumar 1)", total)));
+            }
+            let dists = components.iter().enumerate()
+                .map(|(i, c)| invertible(&format!("componente {}", i + 1), &c.distribution))
+                .collect::<Result<Vec<_>, Error>>()?;
+            for _ in 0..req.count {
+                let u = tape.next();
+                let mut acc = 0.0;
+                let mut chosen = components.len() - 1;
+                for (i, c) in components.iter().enumerate() {
+                    acc += c.weight;
+                    if u < acc {
+                        chosen = i;
+                        break;
+                    }
+                }
+                let x = dists[chosen].quantile(tape.next());
+                record(x, tape.take(), None, Some(chosen), &mut steps);
+            }
+            "composition"
+        },
+    };
+
+    let acceptance_rate = theoretical_acceptance.map(|_| req.count as f64 / total_trials as f64);
+    let (mean, variance) = sample_mean_var(&values);
+    Ok(MethodResponse {
+        method,
+        seed,
+        values,
+        steps,
+        uniforms_used,
+        acceptance_rate,
+        theoretical_acceptance,
+        mean,
+        variance,
+    })
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn generation_method_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, generate_with_method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const N: usize = 20_000;
+
+    fn run(method: &str) -> MethodResponse {
+        let json = format!(r#"{{{}, "count": {}, "seed": 11, "steps_returned": 5}}"#, method, N);
+        generate_with_method(&serde_json::from_str(&json).unwrap()).unwrap()
+    }
+
+    // La media muestral debe quedar a menos de 4 errores estándar de la teórica
+    fn assert_mean(r: &MethodResponse, mean: f64, variance: f64) {
+        let tol = 4.0 * (variance / N as f64).sqrt();
+        assert!((r.mean - mean).abs() < tol, "media {} vs {}", r.mean, mean);
+        assert!((r.variance / variance - 1.0).abs() < 0.1, "varianza {} vs {}", r.variance, variance);
+    }
+
+    // Proporción de aceptación: binomial con p = 1/c
+    fn assert_acceptance(r: &MethodResponse, expected: f64) {
+        assert!((r.theoretical_acceptance.unwrap() - expected).abs() < 1e-12);
+        let rate = r.acceptance_rate.unwrap();
+        let trials = N as f64 / rate;
+        assert!((rate - expected).abs() < 4.0 * (expected * (1.0 - expected) / trials).sqrt(), "{} vs {}", rate, expected);
+    }
+
+    #[test]
+    fn acceptance_rejection_matches_theory() {
+        // Beta(2, 2): M = f(1/2) = 3/2 sobre [0, 1], aceptación 1/c = 2/3
+        let r = run(r#""method": "acceptance_rejection", "distribution": {"type": "beta", "alpha": 2, "beta": 2}"#);
+        assert_acceptance(&r, 2.0 / 3.0);
+        assert_mean(&r, 0.5, 0.05);
+        assert!(r.values.iter().all(|x| (0.0..=1.0).contains(x)));
+        // Dos uniformes por intento
+        assert_eq!(r.steps[0].uniforms.len(), 2 * r.steps[0].trials.unwrap());
+
+        // Normal con envolvente exponencial: sqrt(pi / 2e)
+        let r = run(r#""method": "acceptance_rejection", "distribution": {"type": "normal", "mean": 3, "std": 2}"#);
+        assert_acceptance(&r, (PI / (2.0 * E)).sqrt());
+        assert_mean(&r, 3.0, 4.0);
+    }
+
+    #[test]
+    fn convolution_means() {
+        let r = run(r#""method": "convolution", "distribution": {"type": "erlang", "k": 3, "beta": 2}"#);
+        assert_mean(&r, 6.0, 12.0);
+        assert_eq!(r.uniforms_used, 3 * N);
+        let r = run(r#""method": "convolution", "distribution": {"type": "binomial", "n": 10, "p": 0.3}"#);
+        assert_mean(&r, 3.0, 2.1);
+        assert!(r.values.iter().all(|x| x.fract() == 0.0 && (0.0..=10.0).contains(x)));
+        let r = run(r#""method": "convolution", "distribution": {"type": "normal", "mean": 5, "std": 2}"#);
+        assert_mean(&r, 5.0, 4.0);
+        assert_eq!(r.uniforms_used, 12 * N);
+        // Fallas antes del r-ésimo éxito: r (1 - p) / p
+        let r = run(r#""method": "convolution", "distribution": {"type": "negative_binomial", "r": 3, "p": 0.4}"#);
+        assert_mean(&r, 4.5, 3.0 * 0.6 / 0.16);
+    }
+
+    #[test]
+    fn composition_mean_and_weights() {
+        // 0.3 Exp(media 1) + 0.7 U(10, 20): media 10.8, varianza 0.3·2 + 0.7·(225 + 100/12) - 10.8²
+        let r = run(r#""method": "composition", "components": [
+            {"weight": 0.3, "distribution": {"type": "exponential", "beta": 1}},
+            {"weight": 0.7, "distribution": {"type": "uniform", "min": 10, "max": 20}}]"#);
+        assert_mean(&r, 10.8, 0.6 + 0.7 * (225.0 + 100.0 / 12.0) - 10.8 * 10.8);
+        assert_eq!(r.uniforms_used, 2 * N);
+        let first = r.values.iter().filter(|&&x| x < 10.0).count() as f64 / N as f64;
+        assert!((first - 0.3).abs() < 4.0 * (0.21 / N as f64).sqrt());
+        assert!(r.steps.iter().all(|s| s.component == Some(if s.value < 10.0 { 0 } else { 1 })));
+    }
+
+    #[test]
+    fn rejects_unsupported_targets() {
+        let err = |method: &str| {
+            let json = format!(r#"{{{}, "count": 10, "seed": 1}}"#, method);
+            generate_with_method(&serde_json::from_str(&json).unwrap()).is_err()
+        };
+        assert!(err(r#""method": "acceptance_rejection", "distribution": {"type": "beta", "alpha": 0.5, "beta": 2}"#));
+        assert!(err(r#""method": "acceptance_rejection", "distribution": {"type": "poisson", "lambda": 2}"#));
+        assert!(err(r#""method": "convolution", "distribution": {"type": "negative_binomial", "r": 2.5, "p": 0.4}"#));
+        assert!(err(r#""method": "composition", "components": [{"weight": 0.5, "distribution": {"type": "poisson", "lambda": 2}}]"#));
+    }
+}
@@ -1,2 +1,7 @@
 // src/sampling/mod.rs
-pub mod generator;
\ No newline at end of file
+pub mod generator;
+pub mod processes;
+pub mod prng;
+pub mod randomness;
+pub mod methods;
+pub mod quasi;
\ No newline at end of file
@@ -0,0 +1,483 @@
+// src/sampling/prng.rs
+// Generadores pseudoaleatorios "de clase": congruencial mixto, multiplicativo y cuadrados medios.
+// Pensados para verificar a mano cada paso, no para simular (para eso está ChaCha20).
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::json_helpers::run_json;
+
+// Tope de iteraciones al buscar el periodo (evita recorrer 2^32 estados)
+const DEFAULT_PERIOD_LIMIT: u64 = 10_000_000;
+
+fn default_count() -> usize {
+    20
+}
+
+fn default_period_limit() -> u64 {
+    DEFAULT_PERIOD_LIMIT
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum PrngSpec {
+    // X_{n+1} = (a·X_n + c) mod m
+    Mixed { seed: u64, a: u64, c: u64, m: u64 },
+    // X_{n+1} = (a·X_n) mod m
+    Multiplicative { seed: u64, a: u64, m: u64 },
+    // Von Neumann: se eleva al cuadrado y se toman los `digits` dígitos centrales
+    MiddleSquare { seed: u64, digits: u32 },
+}
+
+#[derive(Deserialize)]
+pub struct PrngRequest {
+    #[serde(flatten)]
+    pub spec: PrngSpec,
+    #[serde(default = "default_count")]
+    pub count: usize,
+    #[serde(default = "default_period_limit")]
+    pub period_limit: u64,
+}
+
+#[derive(Serialize)]
+pub struct PrngStep {
+    pub n: usize,
+    pub x: u64,
+    pub u: f64,
+    // Solo cuadrados medios: el cuadrado del valor anterior
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub square: Option<u128>,
+}
+
+#[derive(Serialize)]
+pub struct ConditionCheck {
+    pub description: String,
+    pub holds: bool,
+}
+
+#[derive(Serialize)]
+pub struct PeriodCheck {
+    pub max_period: u64,              // Periodo máximo alcanzable con ese módulo
+    pub conditions: Vec<ConditionCheck>,
+    pub full_period: bool,            // Se cumplen todas las condiciones
+}
+
+#[derive(Serialize)]
+pub struct PrngResponse {
+    pub method: &'static str,
+    pub sequence: Vec<PrngStep>,
+    pub period: Option<u64>,          // None si supera period_limit
+    pub tail_length: Option<u64>,     // Pasos antes de entrar al ciclo
+    pub degenerates_to_zero: bool,    // Típico de cuadrados medios
+    pub period_check: Option<PeriodCheck>,
+}
+
+impl PrngSpec {
+    fn validate(&self) -> Result<(), Error> {
+        match *self {
+            PrngSpec::Mixed { seed, a, c, m } => {
+                if m < 2 { return Err(Error::Other("m debe ser >= 2".into())); }
+                if a == 0 || a >= m || c >= m || seed >= m {
+                    return Err(Error::Other("Se requiere 0 < a < m, 0 <= c < m y 0 <= X0 < m".into()));
+                }
+            },
+            PrngSpec::Multiplicative { seed, a, m } => {
+                if m < 2 { return Err(Error::Other("m debe ser >= 2".into())); }
+                if a == 0 || a >= m || seed == 0 || seed >= m {
+                    return Err(Error::Other("Se requiere 0 < a < m y 0 < X0 < m".into()));
+                }
+            },
+            PrngSpec::MiddleSquare { seed, digits } => {
+                if digits == 0 || digits % 2 != 0 || digits > 18 {
+                    return Err(Error::Other("digits debe ser par y estar entre 2 y 18".into()));
+                }
+                if seed >= 10u64.pow(digits) {
+                    return Err(Error::Other(format!("La semilla debe tener a lo sumo {} dígitos", digits)));
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PrngSpec::Mixed { .. } => "mixed",
+            PrngSpec::Multiplicative { .. } => "multiplicative",
+            PrngSpec::MiddleSquare { .. } => "middle_square",
+        }
+    }
+
+    fn seed(&self) -> u64 {
+        match *self {
+            PrngSpec::Mixed { seed, .. } | PrngSpec::Multiplicative { seed, .. } | PrngSpec::MiddleSquare { seed, .. } => seed,
+        }
+    }
+
+    /// Siguiente estado.
+    fn next(&self, x: u64) -> u64 {
+        match *self {
+            PrngSpec::Mixed { a, c, m, .. } => ((a as u128 * x as u128 + c as u128) % m as u128) as u64,
+            PrngSpec::Multiplicative { a, m, .. } => ((a as u128 * x as u128) % m as u128) as u64,
+            PrngSpec::MiddleSquare { digits, .. } => {
+                let sq = x as u128 * x as u128;
+                ((sq / 10u128.pow(digits / 2)) % 10u128.pow(digits)) as u64
+            },
+        }
+    }
+
+    /// Normalización a (0, 1): X/m en los congruenciales, X/10^d en cuadrados medios.
+    fn unit(&self, x: u64) -> f64 {
+        match *self {
+            PrngSpec::Mixed { m, .. } | PrngSpec::Multiplicative { m, .. } => x as f64 / m as f64,
+            PrngSpec::MiddleSquare { digits, .. } => x as f64 / 10f64.powi(digits as i32),
+        }
+    }
+}
+
+/// Genera `count` valores a partir de la semilla (X_1 .. X_count).
+pub fn sequence(spec: &PrngSpec, count: usize) -> Vec<PrngStep> {
+    let mut x = spec.seed();
+    (1..=count)
+        .map(|n| {
+            let square = match spec {
+                PrngSpec::MiddleSquare { .. } => Some(x as u128 * x as u128),
+                _ => None,
+            };
+            x = spec.next(x);
+            PrngStep { n, x, u: spec.unit(x), square }
+        })
+        .collect()
+}
+
+/// Algoritmo de Brent: devuelve (periodo, cola) o None si se supera `limit` iteraciones.
+fn detect_cycle(spec: &PrngSpec, limit: u64) -> Option<(u64, u64)> {
+    let x0 = spec.seed();
+    let mut power = 1u64;
+    let mut lambda = 1u64;
+    let mut tortoise = x0;
+    let mut hare = spec.next(x0);
+    let mut steps = 1u64;
+    while tortoise != hare {
+        if power == lambda {
+            tortoise = hare;
+            power *= 2;
+            lambda = 0;
+        }
+        hare = spec.next(hare);
+        lambda += 1;
+        steps += 1;
+        if steps > limit { return None; }
+    }
+
+    // Longitud de la cola: dos punteros separados por lambda pasos
+    let mut tortoise = x0;
+    let mut hare = x0;
+    for _ in 0..lambda {
+        hare = spec.next(hare);
+    }
+    let mut mu = 0u64;
+    while tortoise != hare {
+        tortoise = spec.next(tortoise);
+        hare = spec.next(hare);
+        mu += 1;
+    }
+    Some((lambda, mu))
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// Factores primos distintos de n, en orden creciente: división por tentativa para los
+/// primos pequeños y Pollard rho para el resto (m cercano a 2^64 no requiere 2^32 divisiones).
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = Vec::new();
+    let mut d = 2u64;
+    while d < 1_000 && d <= n / d {
+        if n.is_multiple_of(d) {
+            factors.push(d);
+            while n.is_multiple_of(d) {
+                n /= d;
+            }
+        }
+        d += if d == 2 { 1 } else { 2 };
+    }
+    let mut pending = if n > 1 { vec![n] } else { Vec::new() };
+    while let Some(k) = pending.pop() {
+        if is_prime(k) {
+            factors.push(k);
+        } else {
+            let f = pollard_rho(k);
+            pending.push(f);
+            pending.push(k / f);
+        }
+    }
+    factors.sort_unstable();
+    factors.dedup();
+    factors
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// Miller–Rabin determinista para u64 (bases = primeros 12 primos).
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    for &p in &BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Un factor no trivial de n compuesto e impar (Pollard rho con ciclo de Brent, f(x) = x² + c).
+fn pollard_rho(n: u64) -> u64 {
+    let mut c = 1u64;
+    loop {
+        let f = |x: u64| ((mul_mod(x, x, n) as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut g) = (2u64, 2u64, 1u64);
+        let mut power = 1u64;
+        let mut lam = 0u64;
+        while g == 1 {
+            if power == lam {
+                x = y;
+                power *= 2;
+                lam = 0;
+            }
+            y = f(y);
+            lam += 1;
+            g = gcd(x.abs_diff(y), n);
+        }
+        if g != n {
+            return g;
+        }
+        // El ciclo se cerró sin separar factores: se reintenta con otra constante.
+        c += 1;
+    }
+}
+
+fn pow_mod(base: u64, mut exp: u64, m: u64) -> u64 {
+    let m = m as u128;
+    let mut result = 1u128 % m;
+    let mut b = base as u128 % m;
+    while exp > 0 {
+        if exp & 1 == 1 { result = result * b % m; }
+        b = b * b % m;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// Función de Carmichael λ(m): el mayor orden multiplicativo posible módulo m.
+fn carmichael(m: u64) -> u64 {
+    let mut lambda = 1u64;
+    for q in prime_factors(m) {
+        let mut k = 0u32;
+        let mut rest = m;
+        while rest.is_multiple_of(q) {
+            rest /= q;
+            k += 1;
+        }
+        let term = if q == 2 && k >= 3 { 1u64 << (k - 2) } else { (q - 1) * q.pow(k - 1) };
+        lambda = lambda / gcd(lambda, term) * term;
+    }
+    lambda
+}
+
+fn check(description: String, holds: bool) -> ConditionCheck {
+    ConditionCheck { description, holds }
+}
+
+/// Teorema de Hull–Dobell para el congruencial mixto (periodo completo = m).
+fn hull_dobell(a: u64, c: u64, m: u64) -> PeriodCheck {
+    let factors = prime_factors(m);
+    let a1 = a - 1;
+    let conditions = vec![
+        check(format!("mcd(c, m) = mcd({}, {}) = 1", c, m), gcd(c, m) == 1),
+        check(
+            format!("a - 1 = {} es divisible por cada factor primo de m {:?}", a1, factors),
+            factors.iter().all(|&q| a1.is_multiple_of(q)),
+        ),
+        check(
+            "Si 4 divide a m, entonces 4 divide a a - 1".to_string(),
+            !m.is_multiple_of(4) || a1.is_multiple_of(4),
+        ),
+    ];
+    let full_period = conditions.iter().all(|c| c.holds);
+    PeriodCheck { max_period: m, conditions, full_period }
+}
+
+/// Condiciones de periodo máximo del multiplicativo. Con m = 2^k se usan las reglas clásicas
+/// (a ≡ 3, 5 mod 8 y X0 impar, periodo m/4); en general el máximo es λ(m) y se alcanza si
+/// X0 es primo relativo con m y a tiene orden λ(m) (con m primo: a es raíz primitiva).
+fn multiplicative_check(seed: u64, a: u64, m: u64) -> PeriodCheck {
+    if m.is_power_of_two() && m >= 8 {
+        let conditions = vec![
+            check(format!("a mod 8 = {} ∈ {{3, 5}}", a % 8), a % 8 == 3 || a % 8 == 5),
+            check(format!("X0 = {} es impar", seed), seed % 2 == 1),
+        ];
+        let full_period = conditions.iter().all(|c| c.holds);
+        return PeriodCheck { max_period: m / 4, conditions, full_period };
+    }
+
+    let lambda = carmichael(m);
+    let has_max_order = gcd(a, m) == 1
+        && prime_factors(lambda).iter().all(|&q| pow_mod(a, lambda / q, m) != 1);
+    let factors = prime_factors(m);
+    let order_text = if factors.len() == 1 && factors[0] == m {
+        format!("a = {} es raíz primitiva módulo {} (m primo)", a, m)
+    } else {
+        format!("a = {} tiene orden λ(m) = {} módulo {}", a, lambda, m)
+    };
+    let conditions = vec![
+        check(format!("mcd(X0, m) = mcd({}, {}) = 1", seed, m), gcd(seed, m) == 1),
+        check(order_text, has_max_order),
+    ];
+    let full_period = conditions.iter().all(|c| c.holds);
+    PeriodCheck { max_period: lambda, conditions, full_period }
+}
+
+pub fn generate(req: &PrngRequest) -> Result<PrngResponse, Error> {
+    let spec = &req.spec;
+    spec.validate()?;
+    let sequence = sequence(spec, req.count);
+    let cycle = detect_cycle(spec, req.period_limit);
+
+    // Un ciclo de longitud 1 en el 0 es el "colapso" clásico de cuadrados medios
+    let degenerates_to_zero = match cycle {
+        Some((1, tail)) => {
+            let mut x = spec.seed();
+            for _ in 0..tail {
+                x = spec.next(x);
+            }
+            x == 0
+        },
+        _ => false,
+    };
+
+    let period_check = match *spec {
+        PrngSpec::Mixed { a, c, m, .. } => Some(hull_dobell(a, c, m)),
+        PrngSpec::Multiplicative { seed, a, m } => Some(multiplicative_check(seed, a, m)),
+        PrngSpec::MiddleSquare { .. } => None,
+    };
+
+    Ok(PrngResponse {
+        method: spec.name(),
+        sequence,
+        period: cycle.map(|(l, _)| l),
+        tail_length: cycle.map(|(_, mu)| mu),
+        degenerates_to_zero,
+        period_check,
+    })
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn prng_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, generate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(json: &str) -> PrngResponse {
+        generate(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn mixed_lcg_with_full_period() {
+        // Hull–Dobell con m = 16, a = 5, c = 3: mcd(3, 16) = 1, 2 | 4 y 4 | 4
+        let r = run(r#"{"method": "mixed", "seed": 7, "a": 5, "c": 3, "m": 16, "count": 3}"#);
+        assert_eq!(r.sequence.iter().map(|s| s.x).collect::<Vec<_>>(), vec![6, 1, 8]);
+        assert_eq!(r.sequence[0].u, 6.0 / 16.0);
+        assert_eq!((r.period, r.tail_length), (Some(16), Some(0)));
+        let check = r.period_check.unwrap();
+        assert!(check.full_period && check.conditions.iter().all(|c| c.holds));
+        assert_eq!(check.max_period, 16);
+
+        // a - 1 = 2 no es múltiplo de 4
+        let r = run(r#"{"method": "mixed", "seed": 7, "a": 3, "c": 3, "m": 16}"#);
+        assert!(!r.period_check.unwrap().full_period);
+        assert!(r.period.unwrap() < 16);
+    }
+
+    #[test]
+    fn multiplicative_lcg_periods() {
+        // m = 2^k: periodo máximo 2^(k-2) con a ≡ 3, 5 (mod 8) y X0 impar
+        let r = run(r#"{"method": "multiplicative", "seed": 1, "a": 5, "m": 64}"#);
+        assert_eq!(r.period, Some(16));
+        let check = r.period_check.unwrap();
+        assert!(check.full_period);
+        assert_eq!(check.max_period, 16);
+        for k in 3..=63 {
+            assert_eq!(carmichael(1 << k), 1 << (k - 2));
+        }
+
+        // m primo: 2 es raíz primitiva módulo 13, 3 tiene orden 3
+        let r = run(r#"{"method": "multiplicative", "seed": 1, "a": 2, "m": 13}"#);
+        assert_eq!(r.period, Some(12));
+        assert!(r.period_check.unwrap().full_period);
+        let r = run(r#"{"method": "multiplicative", "seed": 1, "a": 3, "m": 13}"#);
+        assert_eq!(r.period, Some(3));
+        assert!(!r.period_check.unwrap().full_period);
+
+        assert_eq!(carmichael(15), 4);
+        assert_eq!(carmichael(100), 20);
+        assert_eq!(carmichael(561), 80);
+    }
+
+    #[test]
+    fn middle_square_collapses() {
+        // 1000² = 01000000 -> 0000
+        let r = run(r#"{"method": "middle_square", "seed": 1000, "digits": 4}"#);
+        assert_eq!((r.period, r.tail_length), (Some(1), Some(1)));
+        assert!(r.degenerates_to_zero);
+        assert!(r.period_check.is_none());
+
+        // Ciclo clásico 6100 -> 2100 -> 4100 -> 8100 -> 6100
+        let r = run(r#"{"method": "middle_square", "seed": 6100, "digits": 4, "count": 4}"#);
+        assert_eq!(r.sequence.iter().map(|s| s.x).collect::<Vec<_>>(), vec![2100, 4100, 8100, 6100]);
+        assert_eq!(r.sequence[0].square, Some(37_210_000));
+        assert_eq!((r.period, r.tail_length), (Some(4), Some(0)));
+        assert!(!r.degenerates_to_zero);
+
+        // Periodo por encima del límite
+        let r = run(r#"{"method": "mixed", "seed": 0, "a": 5, "c": 3, "m": 1048576, "period_limit": 1000}"#);
+        assert_eq!(r.period, None);
+    }
+
+    #[test]
+    fn factors_large_moduli() {
+        assert_eq!(prime_factors(1), Vec::<u64>::new());
+        assert_eq!(prime_factors(360), vec![2, 3, 5]);
+        assert_eq!(prime_factors(u64::MAX), vec![3, 5, 17, 257, 641, 65_537, 6_700_417]);
+        // (2^32 - 5)(2^32 - 17) y (2^32 - 5)²: solo Pollard rho los separa rápido
+        assert_eq!(prime_factors(4_294_967_291 * 4_294_967_279), vec![4_294_967_279, 4_294_967_291]);
+        assert_eq!(prime_factors(4_294_967_291 * 4_294_967_291), vec![4_294_967_291]);
+        assert_eq!(prime_factors(18_446_744_073_709_551_557), vec![18_446_744_073_709_551_557]);
+        assert!(is_prime(2) && is_prime(4_294_967_291) && !is_prime(561) && !is_prime(1));
+    }
+}
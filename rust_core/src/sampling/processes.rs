@@ -0,0 +1,382 @@
+// src/sampling/processes.rs
+// Simuladores de procesos estocásticos (módulo "Aprender"): trayectorias + estadísticos vs teoría.
+// Los ensayos, huecos y pasos gaussianos se generan con los muestreadores de
+// probabilities::distribution (los mismos que usa el generador de variables).
+use rand::distributions::Open01;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rand_distr::Distribution as _;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::json_helpers::run_json;
+use crate::probabilities::distribution::{Dist, Distribution};
+
+fn default_paths_returned() -> usize {
+    10
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "process", rename_all = "snake_case")]
+pub enum ProcessSpec {
+    Bernoulli { p: f64, n_steps: usize },
+    // Pasos +1 con probabilidad p y -1 con 1-p. Las barreras son absorbentes;
+    // con ambas se obtiene la ruina del jugador.
+    RandomWalk {
+        p: f64,
+        n_steps: usize,
+        #[serde(default)]
+        start: i64,
+        #[serde(default)]
+        lower_barrier: Option<i64>,
+        #[serde(default)]
+        upper_barrier: Option<i64>,
+    },
+    Poisson { rate: f64, horizon: f64 },
+    Brownian { drift: f64, sigma: f64, horizon: f64, n_steps: usize },
+    GeometricBrownian { s0: f64, drift: f64, sigma: f64, horizon: f64, n_steps: usize },
+}
+
+#[derive(Deserialize)]
+pub struct ProcessRequest {
+    #[serde(flatten)]
+    pub spec: ProcessSpec,
+    pub n_paths: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default = "default_paths_returned")]
+    pub paths_returned: usize, // Trayectorias incluidas en la respuesta
+}
+
+#[derive(Serialize)]
+pub struct ProcessPath {
+    pub times: Vec<f64>,
+    pub values: Vec<f64>,
+}
+
+#[derive(Serialize)]
+pub struct StatComparison {
+    pub name: &'static str,
+    pub simulated: f64,
+    pub theoretical: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct ProcessResponse {
+    pub process: &'static str,
+    pub seed: u64,
+    pub n_paths: usize,
+    pub paths: Vec<ProcessPath>,
+    pub statistics: Vec<StatComparison>,
+}
+
+fn stat(name: &'static str, simulated: f64, theoretical: Option<f64>) -> StatComparison {
+    StatComparison { name, simulated, theoretical }
+}
+
+fn mean_var(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len() as f64;
+    if n == 0.0 { return (f64::NAN, f64::NAN); }
+    let mean = xs.iter().sum::<f64>() / n;
+    let var = if n > 1.0 { xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0) } else { 0.0 };
+    (mean, var)
+}
+
+pub fn simulate_process(req: &ProcessRequest) -> Result<ProcessResponse, Error> {
+    if req.n_paths == 0 { return Err(Error::NullOrEmptyInput); }
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    // Cada trayectoria usa su propio stream ChaCha: reproducible con la misma semilla
+    let rng_for = |path: usize| {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        rng.set_stream(path as u64);
+        rng
+    };
+    let keep = req.paths_returned.min(req.n_paths);
+    let mut paths = Vec::with_capacity(keep);
+
+    let (process, statistics) = match req.spec {
+        ProcessSpec::Bernoulli { p, n_steps } => {
+            if !(0.0..=1.0).contains(&p) { return Err(Error::Other("p debe estar en [0, 1]".into())); }
+            if n_steps == 0 { return Err(Error::Other("n_steps debe ser >= 1".into())); }
+            let trial = Dist::Bernoulli { p }.sampler();
+            let mut successes = Vec::with_capacity(req.n_paths);
+            let mut first_success = Vec::new();
+            let mut no_success = 0usize;
+            for path in 0..req.n_paths {
+                let mut rng = rng_for(path);
+                let mut count = 0.0;
+                let mut first = None;
+                let mut values = Vec::with_capacity(if path < keep { n_steps + 1 } else { 0 });
+                if path < keep { values.push(0.0); }
+                for step in 1..=n_steps {
+                    if trial.sample(&mut rng) == 1.0 {
+                        count += 1.0;
+                        first.get_or_insert(step as f64);
+                    }
+                    if path < keep { values.push(count); }
+                }
+                successes.push(count);
+                if first.is_none() { no_success += 1; }
+                // Sin éxito en n_steps, la espera restante (sin memoria) se genera de una vez por
+                // inversión de la geométrica (fracasos + 1), para no truncarla. Un bucle por
+                // ensayo no terminaría con p < 2^-64 (Bernoulli redondea p a 0).
+                if p > 0.0 {
+                    let t = first.unwrap_or_else(|| {
+                        let u: f64 = rng.sample(Open01);
+                        n_steps as f64 + Dist::Geometric { p }.quantile(u) + 1.0
+                    });
+                    first_success.push(t);
+                }
+                if path < keep { paths.push(ProcessPath { times: (0..=n_steps).map(|t| t as f64).collect(), values }); }
+            }
+            let n = n_steps as f64;
+            let (m, v) = mean_var(&successes);
+            let (mf, _) = mean_var(&first_success);
+            ("bernoulli", vec![
+                stat("successes_mean", m, Some(n * p)),
+                stat("successes_variance", v, Some(n * p * (1.0 - p))),
+                stat("first_success_mean", mf, if p > 0.0 { Some(1.0 / p) } else { None }),
+                stat("no_success_fraction", no_success as f64 / req.n_paths as f64, Some((1.0 - p).powf(n))),
+            ])
+        },
+        ProcessSpec::RandomWalk { p, n_steps, start, lower_barrier, upper_barrier } => {
+            if !(0.0..=1.0).contains(&p) { return Err(Error::Other("p debe estar en [0, 1]".into())); }
+            if n_steps == 0 { return Err(Error::Other("n_steps debe ser >= 1".into())); }
+            if lower_barrier.is_some_and(|lo| lo >= start) || upper_barrier.is_some_and(|hi| hi <= start) {
+                return Err(Error::Other("El inicio debe estar estrictamente entre las barreras".into()));
+            }
+            let step_up = Dist::Bernoulli { p }.sampler();
+            let mut finals = Vec::with_capacity(req.n_paths);
+            let mut hit_lower = 0usize;
+            let mut hit_upper = 0usize;
+            let mut absorption_times = Vec::new();
+            for path in 0..req.n_paths {
+                let mut rng = rng_for(path);
+                let mut pos = start;
+                let mut values = Vec::new();
+                if path < keep { values.push(pos as f64); }
+                let mut steps_taken = n_steps;
+                for step in 1..=n_steps {
+                    pos += if step_up.sample(&mut rng) == 1.0 { 1 } else { -1 };
+                    if path < keep { values.push(pos as f64); }
+                    if lower_barrier == Some(pos) { hit_lower += 1; }
+                    if upper_barrier == Some(pos) { hit_upper += 1; }
+                    if lower_barrier == Some(pos) || upper_barrier == Some(pos) {
+                        absorption_times.push(step as f64);
+                        steps_taken = step;
+                        break;
+                    }
+                }
+                finals.push(pos as f64);
+                if path < keep { paths.push(ProcessPath { times: (0..=steps_taken).map(|t| t as f64).collect(), values }); }
+            }
+            let q = 1.0 - p;
+            let n = n_steps as f64;
+            let total = req.n_paths as f64;
+            let (m, v) = mean_var(&finals);
+            let (mt, _) = mean_var(&absorption_times);
+            let mut stats = Vec::new();
+            if lower_barrier.is_none() && upper_barrier.is_none() {
+                stats.push(stat("final_mean", m, Some(start as f64 + n * (p - q))));
+                stats.push(stat("final_variance", v, Some(4.0 * n * p * q)));
+            } else {
+                stats.push(stat("final_mean", m, None));
+            }
+            match (lower_barrier, upper_barrier) {
+                (Some(lo), Some(hi)) => {
+                    // Ruina del jugador: capital k = start - lo, meta N = hi - lo
+                    let k = (start - lo) as f64;
+                    let goal = (hi - lo) as f64;
+                    let (ruin, duration) = gamblers_ruin(p, k, goal);
+                    stats.push(stat("ruin_probability", hit_lower as f64 / total, Some(ruin)));
+                    stats.push(stat("expected_duration", mt, Some(duration)));
+                    stats.push(stat("unabsorbed_fraction", 1.0 - (hit_lower + hit_upper) as f64 / total, None));
+                },
+                (lo, hi) => {
+                    // Primer paso por una sola barrera a distancia a: E[T] = a / |p - q| si la deriva la favorece
+                    if let Some(level) = lo.or(hi) {
+                        let a = (level - start).abs() as f64;
+                        let towards = if level > start { p - q } else { q - p };
+                        stats.push(stat("first_passage_fraction", (hit_lower + hit_upper) as f64 / total, None));
+                        stats.push(stat("first_passage_mean", mt, if towards > 0.0 { Some(a / towards) } else { None }));
+                    }
+                },
+            }
+            ("random_walk", stats)
+        },
+        ProcessSpec::Poisson { rate, horizon } => {
+            if rate <= 0.0 || horizon <= 0.0 { return Err(Error::Other("rate y horizon deben ser > 0".into())); }
+            let gaps = Dist::Exponential { beta: 1.0 / rate }.sampler();
+            let mut counts = Vec::with_capacity(req.n_paths);
+            for path in 0..req.n_paths {
+                let mut rng = rng_for(path);
+                let mut t = 0.0;
+                let mut times = vec![0.0];
+                let mut count = 0usize;
+                loop {
+                    let gap = gaps.sample(&mut rng);
+                    if t + gap > horizon { break; }
+                    t += gap;
+                    count += 1;
+                    if path < keep { times.push(t); }
+                }
+                counts.push(count as f64);
+                if path < keep {
+                    let values = (0..times.len()).map(|k| k as f64).collect();
+                    paths.push(ProcessPath { times, values });
+                }
+            }
+            let (m, v) = mean_var(&counts);
+            // Estimador T·n / N: la media de los huecos observados dentro de [0, T] está sesgada a la baja
+            let events: f64 = counts.iter().sum();
+            let mg = if events > 0.0 { horizon * req.n_paths as f64 / events } else { f64::NAN };
+            ("poisson", vec![
+                stat("count_mean", m, Some(rate * horizon)),
+                stat("count_variance", v, Some(rate * horizon)),
+                stat("interarrival_mean", mg, Some(1.0 / rate)),
+            ])
+        },
+        ProcessSpec::Brownian { drift, sigma, horizon, n_steps } => {
+            let finals = simulate_gaussian_paths(req, &rng_for, &mut paths, keep, sigma, horizon, n_steps, |w, t| drift * t + sigma * w)?;
+            let (m, v) = mean_var(&finals);
+            ("brownian", vec![
+                stat("final_mean", m, Some(drift * horizon)),
+                stat("final_variance", v, Some(sigma * sigma * horizon)),
+            ])
+        },
+        ProcessSpec::GeometricBrownian { s0, drift, sigma, horizon, n_steps } => {
+            if s0 <= 0.0 { return Err(Error::Other("s0 debe ser > 0".into())); }
+            let finals = simulate_gaussian_paths(req, &rng_for, &mut paths, keep, sigma, horizon, n_steps,
+                |w, t| s0 * ((drift - 0.5 * sigma * sigma) * t + sigma * w).exp())?;
+            let (m, v) = mean_var(&finals);
+            let s2t = sigma * sigma * horizon;
+            ("geometric_brownian", vec![
+                stat("final_mean", m, Some(s0 * (drift * horizon).exp())),
+                stat("final_variance", v, Some(s0 * s0 * (2.0 * drift * horizon).exp() * (s2t.exp() - 1.0))),
+            ])
+        },
+    };
+
+    Ok(ProcessResponse { process, seed, n_paths: req.n_paths, paths, statistics })
+}
+
+/// Trayectorias X(t) = f(W(t), t) sobre una malla regular, con W un movimiento browniano estándar.
+#[allow(clippy::too_many_arguments)]
+fn simulate_gaussian_paths<F: Fn(f64, f64) -> f64>(
+    req: &ProcessRequest,
+    rng_for: &dyn Fn(usize) -> ChaCha20Rng,
+    paths: &mut Vec<ProcessPath>,
+    keep: usize,
+    sigma: f64,
+    horizon: f64,
+    n_steps: usize,
+    f: F,
+) -> Result<Vec<f64>, Error> {
+    if sigma < 0.0 || horizon <= 0.0 || n_steps == 0 {
+        return Err(Error::Other("Se requiere sigma >= 0, horizon > 0 y n_steps >= 1".into()));
+    }
+    let dt = horizon / n_steps as f64;
+    let sqrt_dt = dt.sqrt();
+    let standard_normal = Dist::Normal { mean: 0.0, std: 1.0 }.sampler();
+    let mut finals = Vec::with_capacity(req.n_paths);
+    for path in 0..req.n_paths {
+        let mut rng = rng_for(path);
+        let mut w = 0.0;
+        let mut values = Vec::new();
+        if path < keep { values.push(f(0.0, 0.0)); }
+        for step in 1..=n_steps {
+            w += sqrt_dt * standard_normal.sample(&mut rng);
+            if path < keep { values.push(f(w, step as f64 * dt)); }
+        }
+        finals.push(f(w, horizon));
+        if path < keep {
+            paths.push(ProcessPath { times: (0..=n_steps).map(|k| k as f64 * dt).collect(), values });
+        }
+    }
+    Ok(finals)
+}
+
+/// Probabilidad de ruina y duración esperada con capital k y meta N.
+fn gamblers_ruin(p: f64, k: f64, goal: f64) -> (f64, f64) {
+    let q = 1.0 - p;
+    if p == 0.0 { return (1.0, k); }
+    if (p - q).abs() < 1e-12 { return (1.0 - k / goal, k * (goal - k)); }
+    // Con r = q/p: ruina = (r^k - r^N) / (1 - r^N) y h = (1 - r^k) / (1 - r^N). Si r > 1 se
+    // divide por r^N y se usa s = p/q < 1, para que las potencias no desborden
+    let (ruin, h) = if q < p {
+        let r = q / p;
+        let denom = 1.0 - r.powf(goal);
+        ((r.powf(k) - r.powf(goal)) / denom, (1.0 - r.powf(k)) / denom)
+    } else {
+        let s = p / q;
+        let denom = 1.0 - s.powf(goal);
+        ((1.0 - s.powf(goal - k)) / denom, (s.powf(goal - k) - s.powf(goal)) / denom)
+    };
+    (ruin, k / (q - p) - goal / (q - p) * h)
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn stochastic_process_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, simulate_process)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, rel: f64) {
+        assert!((actual - expected).abs() <= rel * expected.abs().max(1.0), "got {actual}, expected {expected}");
+    }
+
+    fn simulate(json: &str) -> ProcessResponse {
+        simulate_process(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    /// Cada estadístico simulado con teoría debe quedar a menos de `rel` de ella.
+    fn assert_matches_theory(r: &ProcessResponse, rel: f64) {
+        for s in &r.statistics {
+            if let Some(theory) = s.theoretical {
+                assert!((s.simulated - theory).abs() <= rel * theory.abs(), "{}: {} vs {}", s.name, s.simulated, theory);
+            }
+        }
+    }
+
+    #[test]
+    fn gamblers_ruin_closed_forms() {
+        // Simétrica: 1 - k/N y k (N - k)
+        assert_eq!(gamblers_ruin(0.5, 3.0, 10.0), (0.7, 21.0));
+        // p = 0.4, r = 1.5: (r^3 - r^10) / (1 - r^10) y 3/0.2 - 10/0.2 · (1 - r^3) / (1 - r^10)
+        let (ruin, duration) = gamblers_ruin(0.4, 3.0, 10.0);
+        assert_close(ruin, 0.958_087_031_451_960_4, 1e-12);
+        assert_close(duration, 12.904_351_572_598_02, 1e-12);
+    }
+
+    #[test]
+    fn simulated_statistics_match_theory() {
+        let walk = simulate(r#"{"process": "random_walk", "p": 0.4, "n_steps": 100000, "start": 3,
+            "lower_barrier": 0, "upper_barrier": 10, "n_paths": 20000, "seed": 7}"#);
+        assert_matches_theory(&walk, 0.05);
+        let poisson = simulate(r#"{"process": "poisson", "rate": 2.0, "horizon": 5.0, "n_paths": 20000, "seed": 7}"#);
+        assert_matches_theory(&poisson, 0.03);
+        let gbm = simulate(r#"{"process": "geometric_brownian", "s0": 100, "drift": 0.05, "sigma": 0.2,
+            "horizon": 1.0, "n_steps": 50, "n_paths": 20000, "seed": 7}"#);
+        assert_matches_theory(&gbm, 0.05);
+        let bernoulli = simulate(r#"{"process": "bernoulli", "p": 0.2, "n_steps": 10, "n_paths": 20000, "seed": 7}"#);
+        assert_matches_theory(&bernoulli, 0.05);
+    }
+
+    #[test]
+    fn gamblers_ruin_edge_cases_stay_finite() {
+        assert_eq!(gamblers_ruin(0.0, 3.0, 10.0), (1.0, 3.0));
+        let (ruin, duration) = gamblers_ruin(1.0, 3.0, 10.0);
+        assert_eq!(ruin, 0.0);
+        assert_close(duration, 7.0, 1e-12);
+        // r = 9: r^N desborda f64 con N = 400
+        let (ruin, duration) = gamblers_ruin(0.1, 200.0, 400.0);
+        assert_close(ruin, 1.0, 1e-12);
+        assert_close(duration, 200.0 / 0.8, 1e-12);
+        let (ruin, duration) = gamblers_ruin(0.9, 200.0, 400.0);
+        assert!(ruin.is_finite() && ruin < 1e-100);
+        assert_close(duration, 200.0 / 0.8, 1e-12);
+    }
+}
@@ -0,0 +1,330 @@
+// src/sampling/quasi.rs
+// Secuencias cuasi-aleatorias (baja discrepancia): van der Corput, Halton y Sobol.
+// Cada punto se calcula directamente a partir de su índice, así cualquier bloque de
+// iteraciones puede generarse en paralelo sin estado compartido.
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use std::slice;
+use crate::errors::Error;
+use crate::json_helpers::{run_json, to_cstring};
+
+// Direcciones de Sobol (Joe & Kuo, new-joe-kuo-6.21201) para las dimensiones 2..=21:
+// (grado s, coeficiente a, números iniciales m_1..m_s)
+const SOBOL_PARAMS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+pub const MAX_SOBOL_DIMS: usize = SOBOL_PARAMS.len() + 1;
+const BITS: usize = 32;
+// Puntos distintos de Sobol con direcciones de 32 bits
+const SOBOL_PERIOD: u64 = 1 << BITS;
+// Tope de la base de van der Corput (con scramble se permutan sus b dígitos)
+const MAX_BASE: u32 = 1 << 16;
+
+// Tope de puntos para la discrepancia L2 (costo O(n² d))
+const MAX_DISCREPANCY_POINTS: usize = 4096;
+
+fn default_base() -> u32 {
+    2
+}
+
+fn default_dimensions() -> usize {
+    1
+}
+
+fn default_skip() -> u64 {
+    1 // El punto 0 es el origen; se omite por defecto
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuasiKind {
+    VanDerCorput,
+    Halton,
+    Sobol,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct QuasiSpec {
+    pub sequence: QuasiKind,
+    // Halton: permutación aleatoria de dígitos; Sobol: desplazamiento digital aleatorio
+    #[serde(default)]
+    pub scramble: bool,
+    #[serde(default = "default_base")]
+    pub base: u32, // Solo van der Corput
+}
+
+/// Secuencia preparada para `dims` dimensiones.
+pub struct QuasiSequence {
+    kind: QuasiKind,
+    bases: Vec<u32>,
+    permutations: Vec<Vec<u32>>, // Halton con scramble: una permutación por dimensión (0 fijo)
+    directions: Vec<[u32; BITS]>, // Sobol
+    shifts: Vec<u32>,             // Sobol con scramble
+}
+
+fn first_primes(n: usize) -> Vec<u32> {
+    let mut primes: Vec<u32> = Vec::with_capacity(n);
+    let mut candidate = 2u32;
+    while primes.len() < n {
+        if primes.iter().take_while(|&&p| p * p <= candidate).all(|&p| !candidate.is_multiple_of(p)) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// Inverso radical: refleja los dígitos de `index` en base b alrededor del punto decimal.
+fn radical_inverse(mut index: u64, base: u32, perm: Option<&[u32]>) -> f64 {
+    let b = base as u64;
+    let inv_base = 1.0 / base as f64;
+    let mut factor = inv_base;
+    let mut result = 0.0;
+    while index > 0 {
+        let digit = (index % b) as u32;
+        let digit = perm.map_or(digit, |p| p[digit as usize]);
+        result += digit as f64 * factor;
+        index /= b;
+        factor *= inv_base;
+    }
+    result
+}
+
+fn sobol_directions(dim: usize) -> [u32; BITS] {
+    let mut v = [0u32; BITS];
+    if dim == 0 {
+        // Primera dimensión: van der Corput en base 2
+        for (i, vi) in v.iter_mut().enumerate() {
+            *vi = 1 << (BITS - 1 - i);
+        }
+        return v;
+    }
+    let (s, a, m) = SOBOL_PARAMS[dim - 1];
+    let s = s as usize;
+    for i in 0..BITS {
+        v[i] = if i < s {
+            m[i] << (BITS - 1 - i)
+        } else {
+            let mut x = v[i - s] ^ (v[i - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    x ^= v[i - k];
+                }
+            }
+            x
+        };
+    }
+    v
+}
+
+impl QuasiSequence {
+    pub fn new(spec: &QuasiSpec, dims: usize, seed: u64) -> Result<Self, Error> {
+        if dims == 0 { return Err(Error::Other("Se requiere al menos una dimensión".into())); }
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let mut seq = QuasiSequence {
+            kind: spec.sequence,
+            bases: Vec::new(),
+            permutations: Vec::new(),
+            directions: Vec::new(),
+            shifts: Vec::new(),
+        };
+        match spec.sequence {
+            QuasiKind::VanDerCorput | QuasiKind::Halton => {
+                seq.bases = if spec.sequence == QuasiKind::VanDerCorput {
+                    if dims > 1 { return Err(Error::Other("Van der Corput es unidimensional".into())); }
+                    if !(2..=MAX_BASE).contains(&spec.base) {
+                        return Err(Error::Other(format!("La base debe estar entre 2 y {}", MAX_BASE)));
+                    }
+                    vec![spec.base]
+                } else {
+                    first_primes(dims)
+                };
+                if spec.scramble {
+                    seq.permutations = seq.bases.iter().map(|&b| {
+                        let mut digits: Vec<u32> = (1..b).collect();
+                        digits.shuffle(&mut rng);
+                        std::iter::once(0).chain(digits).collect()
+                    }).collect();
+                }
+            },
+            QuasiKind::Sobol => {
+                if dims > MAX_SOBOL_DIMS {
+                    return Err(Error::Other(format!("Sobol admite hasta {} dimensiones", MAX_SOBOL_DIMS)));
+                }
+                seq.directions = (0..dims).map(sobol_directions).collect();
+                seq.shifts = if spec.scramble { (0..dims).map(|_| rng.gen()).collect() } else { vec![0; dims] };
+            },
+        }
+        Ok(seq)
+    }
+
+    pub fn dims(&self) -> usize {
+        match self.kind {
+            QuasiKind::Sobol => self.directions.len(),
+            _ => self.bases.len(),
+        }
+    }
+
+    /// Escribe en `out` el punto número `index` (una coordenada por dimensión). Sobol repite
+    /// la secuencia cada 2^32 puntos (solo hay 32 números de dirección).
+    pub fn point(&self, index: u64, out: &mut [f64]) {
+        match self.kind {
+            QuasiKind::Sobol => {
+                for (d, x) in out.iter_mut().enumerate() {
+                    let v = &self.directions[d];
+                    let mut bits = self.shifts[d];
+                    for (i, vi) in v.iter().enumerate() {
+                        if (index >> i) & 1 == 1 { bits ^= vi; }
+                    }
+                    // Centro de la celda 2^-32: nunca exactamente 0 ni 1
+                    *x = (bits as f64 + 0.5) / 4_294_967_296.0;
+                }
+            },
+            _ => {
+                for (d, x) in out.iter_mut().enumerate() {
+                    let perm = self.permutations.get(d).map(|p| p.as_slice());
+                    *x = radical_inverse(index, self.bases[d], perm);
+                }
+            },
+        }
+    }
+}
+
+/// Discrepancia L2-estrella (fórmula de Warnock) de puntos en filas de `dims` coordenadas.
+pub fn l2_star_discrepancy(points: &[f64], dims: usize) -> f64 {
+    let n = points.len() / dims;
+    if n == 0 { return f64::NAN; }
+    let rows: Vec<&[f64]> = points.chunks_exact(dims).collect();
+    let term1 = 3f64.powi(-(dims as i32));
+    let term2: f64 = rows.iter().map(|r| r.iter().map(|x| (1.0 - x * x) / 2.0).product::<f64>()).sum::<f64>() * 2.0 / n as f64;
+    let mut term3 = 0.0;
+    for a in &rows {
+        for b in &rows {
+            term3 += a.iter().zip(b.iter()).map(|(x, y)| 1.0 - x.max(*y)).product::<f64>();
+        }
+    }
+    (term1 - term2 + term3 / (n * n) as f64).max(0.0).sqrt()
+}
+
+#[derive(Deserialize)]
+pub struct QuasiRequest {
+    #[serde(flatten)]
+    pub spec: QuasiSpec,
+    #[serde(default = "default_dimensions")]
+    pub dimensions: usize,
+    #[serde(default = "default_skip")]
+    pub skip: u64,
+    #[serde(default)]
+    pub seed: Option<u64>, // Solo se usa con scramble
+}
+
+#[derive(Serialize)]
+pub struct DiscrepancyEstimate {
+    pub l2_star: f64,
+    pub points_used: usize,
+    pub expected_random: f64, // E[D²] = (2^-d - 3^-d) / n para puntos pseudoaleatorios
+}
+
+#[derive(Serialize)]
+pub struct QuasiResponse {
+    pub sequence: QuasiKind,
+    pub count: usize,
+    pub dimensions: usize,
+    pub seed: u64,
+    pub discrepancy: DiscrepancyEstimate,
+}
+
+/// Llena `buf` por filas: el punto k ocupa buf[k*d .. (k+1)*d].
+pub fn fill_quasi(buf: &mut [f64], req: &QuasiRequest) -> Result<QuasiResponse, Error> {
+    let dims = req.dimensions;
+    if dims == 0 || !buf.len().is_multiple_of(dims) {
+        return Err(Error::Other("La longitud del buffer debe ser múltiplo de las dimensiones".into()));
+    }
+    let count = buf.len() / dims;
+    let end = req.skip.checked_add(count as u64)
+        .ok_or_else(|| Error::Other("skip + cantidad de puntos desborda el índice".into()))?;
+    if req.spec.sequence == QuasiKind::Sobol && end > SOBOL_PERIOD {
+        return Err(Error::Other(format!("Sobol admite índices menores que 2^{} (skip + cantidad = {})", BITS, end)));
+    }
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let seq = QuasiSequence::new(&req.spec, dims, seed)?;
+    for (k, row) in buf.chunks_exact_mut(dims).enumerate() {
+        seq.point(req.skip + k as u64, row);
+    }
+
+    let used = count.min(MAX_DISCREPANCY_POINTS);
+    let d = dims as i32;
+    let discrepancy = DiscrepancyEstimate {
+        l2_star: l2_star_discrepancy(&buf[..used * dims], dims),
+        points_used: used,
+        expected_random: ((2f64.powi(-d) - 3f64.powi(-d)) / used as f64).sqrt(),
+    };
+    Ok(QuasiResponse { sequence: req.spec.sequence, count, dimensions: dims, seed, discrepancy })
+}
+
+/// Buffer filler descrito por JSON. Devuelve {"count", "dimensions", "discrepancy", ...} o {"error"}.
+pub fn generate_quasi(ptr: *mut f64, len: usize, json_request: *const c_char) -> *mut c_char {
+    if ptr.is_null() || len == 0 {
+        return to_cstring(&serde_json::json!({"error": Error::NullOrEmptyInput.to_string()}));
+    }
+    let buf = unsafe { slice::from_raw_parts_mut(ptr, len) };
+    run_json(json_request, |req: &QuasiRequest| fill_quasi(buf, req))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(json: &str, len: usize) -> Result<Vec<f64>, Error> {
+        let mut buf = vec![0.0; len];
+        let req: QuasiRequest = serde_json::from_str(json).unwrap();
+        fill_quasi(&mut buf, &req).map(|_| buf)
+    }
+
+    #[test]
+    fn van_der_corput_base_2() {
+        let xs = fill(r#"{"sequence": "van_der_corput"}"#, 4).unwrap();
+        assert_eq!(xs, vec![0.5, 0.25, 0.75, 0.125]);
+        assert!(fill(r#"{"sequence": "van_der_corput", "base": 1}"#, 4).is_err());
+        assert!(fill(r#"{"sequence": "van_der_corput", "base": 4294967295, "scramble": true}"#, 4).is_err());
+    }
+
+    #[test]
+    fn sobol_rejects_indices_past_its_period() {
+        let last = fill(r#"{"sequence": "sobol", "skip": 4294967295}"#, 1).unwrap();
+        assert!(last[0] > 0.0 && last[0] < 1.0);
+        assert!(fill(r#"{"sequence": "sobol", "skip": 4294967296}"#, 1).is_err());
+        assert!(fill(r#"{"sequence": "sobol", "skip": 4294967295}"#, 2).is_err());
+        assert!(fill(r#"{"sequence": "halton", "skip": 18446744073709551615}"#, 2).is_err());
+
+        // Más allá del periodo el punto se repite en lugar de leer fuera de las direcciones
+        let seq = QuasiSequence::new(&serde_json::from_str(r#"{"sequence": "sobol"}"#).unwrap(), 1, 0).unwrap();
+        let (mut a, mut b) = ([0.0], [0.0]);
+        seq.point(5, &mut a);
+        seq.point(SOBOL_PERIOD + 5, &mut b);
+        assert_eq!(a, b);
+    }
+}
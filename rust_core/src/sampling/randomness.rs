@@ -0,0 +1,543 @@
+// src/sampling/randomness.rs
+// Pruebas estadísticas de aleatoriedad para secuencias U(0,1): uniformidad, independencia y rachas.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::json_helpers::run_json;
+use crate::probabilities::special::{gamma_q, gamma_q_inv, standard_normal_cdf, standard_normal_quantile};
+use crate::sampling::prng::{self, PrngRequest};
+
+fn default_alpha() -> f64 {
+    0.05
+}
+
+fn default_poker_digits() -> u32 {
+    5
+}
+
+fn default_gap_interval() -> (f64, f64) {
+    (0.0, 0.5)
+}
+
+fn default_gap_max() -> usize {
+    5
+}
+
+fn default_serial_bins() -> usize {
+    4
+}
+
+fn default_one() -> usize {
+    1
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomnessTest {
+    ChiSquare,
+    KolmogorovSmirnov,
+    RunsUpDown,
+    RunsAboveBelow,
+    Poker,
+    Gap,
+    Serial,
+    Autocorrelation,
+}
+
+const ALL_TESTS: [RandomnessTest; 8] = [
+    RandomnessTest::ChiSquare,
+    RandomnessTest::KolmogorovSmirnov,
+    RandomnessTest::RunsUpDown,
+    RandomnessTest::RunsAboveBelow,
+    RandomnessTest::Poker,
+    RandomnessTest::Gap,
+    RandomnessTest::Serial,
+    RandomnessTest::Autocorrelation,
+];
+
+#[derive(Deserialize)]
+pub struct RandomnessRequest {
+    // Números a probar; alternativamente se generan con un PRNG didáctico
+    #[serde(default)]
+    pub values: Vec<f64>,
+    #[serde(default)]
+    pub prng: Option<PrngRequest>,
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+    // Vacío = todas las pruebas
+    #[serde(default)]
+    pub tests: Vec<RandomnessTest>,
+    #[serde(default)]
+    pub bins: Option<usize>,          // Chi-cuadrada; por defecto sqrt(n)
+    #[serde(default = "default_poker_digits")]
+    pub poker_digits: u32,            // 3, 4 o 5 dígitos por mano
+    #[serde(default = "default_gap_interval")]
+    pub gap_interval: (f64, f64),     // [a, b] que define un "éxito" en la prueba de huecos
+    #[serde(default = "default_gap_max")]
+    pub gap_max: usize,               // Huecos >= gap_max se agrupan en una clase
+    #[serde(default = "default_serial_bins")]
+    pub serial_bins: usize,           // Celdas por eje en la prueba serial (k x k)
+    #[serde(default = "default_one")]
+    pub lag: usize,                   // Autocorrelación: separación l
+    #[serde(default = "default_one")]
+    pub start: usize,                 // Autocorrelación: índice inicial i (base 1)
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Pass,   // No se rechaza H0
+    Fail,   // Se rechaza H0
+}
+
+#[derive(Serialize)]
+pub struct ClassCount {
+    pub label: String,
+    pub observed: f64,
+    pub expected: f64,
+}
+
+#[derive(Serialize)]
+pub struct TestResult {
+    pub test: RandomnessTest,
+    pub statistic: f64,
+    pub critical_value: f64,
+    pub p_value: f64,
+    pub df: Option<usize>,
+    pub verdict: Verdict,
+    // Tabla observada vs esperada en las pruebas chi-cuadrada
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub classes: Vec<ClassCount>,
+}
+
+#[derive(Serialize)]
+pub struct RandomnessResponse {
+    pub n: usize,
+    pub alpha: f64,
+    pub results: Vec<TestResult>,
+    pub all_passed: bool,
+}
+
+// --- Distribuciones de referencia ---
+
+fn chi_square_sf(x: f64, df: usize) -> f64 {
+    gamma_q(df as f64 / 2.0, x / 2.0)
+}
+
+fn chi_square_critical(alpha: f64, df: usize) -> f64 {
+    2.0 * gamma_q_inv(df as f64 / 2.0, alpha)
+}
+
+fn z_critical(alpha: f64) -> f64 {
+    // z_{1-alpha/2}
+    -standard_normal_quantile(alpha / 2.0)
+}
+
+/// Inversa por bisección de una función decreciente f(x) = target en [lo, hi].
+fn bisect_decreasing<F: Fn(f64) -> f64>(f: F, target: f64, mut lo: f64, mut hi: f64) -> f64 {
+    while f(hi) > target {
+        hi *= 2.0;
+    }
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if f(mid) > target { lo = mid; } else { hi = mid; }
+        if hi - lo < 1e-12 * hi.max(1.0) { break; }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Distribución asintótica de Kolmogorov: P(K > lambda).
+fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda < 1e-3 { return 1.0; }
+    let mut sum = 0.0;
+    for k in 1..=100 {
+        let kf = k as f64;
+        let term = (-2.0 * kf * kf * lambda * lambda).exp();
+        sum += if k % 2 == 1 { term } else { -term };
+        if term < 1e-16 { break; }
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+// --- Constructores de resultados ---
+
+fn verdict(p_value: f64, alpha: f64) -> Verdict {
+    if p_value < alpha { Verdict::Fail } else { Verdict::Pass }
+}
+
+fn chi_square_result(test: RandomnessTest, classes: Vec<ClassCount>, lost_df: usize, alpha: f64) -> Result<TestResult, Error> {
+    if classes.len() <= lost_df + 1 {
+        return Err(Error::Other(format!("Muy pocas clases para la prueba {}", test_name(test))));
+    }
+    let statistic = classes
+        .iter()
+        .filter(|c| c.expected > 0.0)
+        .map(|c| (c.observed - c.expected).powi(2) / c.expected)
+        .sum();
+    let df = classes.len() - 1 - lost_df;
+    let p_value = chi_square_sf(statistic, df);
+    Ok(TestResult {
+        test,
+        statistic,
+        critical_value: chi_square_critical(alpha, df),
+        p_value,
+        df: Some(df),
+        verdict: verdict(p_value, alpha),
+        classes,
+    })
+}
+
+fn z_result(test: RandomnessTest, z: f64, alpha: f64) -> TestResult {
+    let p_value = 2.0 * standard_normal_cdf(-z.abs());
+    TestResult {
+        test,
+        statistic: z,
+        critical_value: z_critical(alpha),
+        p_value,
+        df: None,
+        verdict: verdict(p_value, alpha),
+        classes: Vec::new(),
+    }
+}
+
+fn test_name(test: RandomnessTest) -> &'static str {
+    match test {
+        RandomnessTest::ChiSquare => "chi_square",
+        RandomnessTest::KolmogorovSmirnov => "kolmogorov_smirnov",
+        RandomnessTest::RunsUpDown => "runs_up_down",
+        RandomnessTest::RunsAboveBelow => "runs_above_below",
+        RandomnessTest::Poker => "poker",
+        RandomnessTest::Gap => "gap",
+        RandomnessTest::Serial => "serial",
+        RandomnessTest::Autocorrelation => "autocorrelation",
+    }
+}
+
+/// Agrupa las clases finales con frecuencia esperada < 5 (regla habitual de la chi-cuadrada).
+fn merge_small_tail(mut classes: Vec<ClassCount>) -> Vec<ClassCount> {
+    while classes.len() > 2 && classes.last().is_some_and(|c| c.expected < 5.0) {
+        let last = classes.pop().unwrap();
+        let prev = classes.last_mut().unwrap();
+        prev.label = format!("{} + {}", prev.label, last.label);
+        prev.observed += last.observed;
+        prev.expected += last.expected;
+    }
+    classes
+}
+
+// --- Pruebas ---
+
+fn chi_square_uniformity(u: &[f64], bins: Option<usize>, alpha: f64) -> Result<TestResult, Error> {
+    let n = u.len();
+    let k = bins.unwrap_or_else(|| ((n as f64).sqrt().round() as usize).max(2));
+    if k < 2 { return Err(Error::Other("bins debe ser >= 2".into())); }
+    let mut observed = vec![0.0; k];
+    for &x in u {
+        observed[((x * k as f64) as usize).min(k - 1)] += 1.0;
+    }
+    let expected = n as f64 / k as f64;
+    let classes = observed
+        .into_iter()
+        .enumerate()
+        .map(|(i, o)| ClassCount {
+            label: format!("[{:.4}, {:.4})", i as f64 / k as f64, (i + 1) as f64 / k as f64),
+            observed: o,
+            expected,
+        })
+        .collect();
+    chi_square_result(RandomnessTest::ChiSquare, classes, 0, alpha)
+}
+
+fn kolmogorov_smirnov(u: &[f64], alpha: f64) -> TestResult {
+    let mut sorted = u.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = sorted.len() as f64;
+    // D = max(D+, D-)
+    let d = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| ((i + 1) as f64 / n - x).max(x - i as f64 / n))
+        .fold(0.0, f64::max);
+    // Corrección de Stephens para n finito
+    let factor = n.sqrt() + 0.12 + 0.11 / n.sqrt();
+    let p_value = kolmogorov_sf(factor * d);
+    let lambda_crit = bisect_decreasing(kolmogorov_sf, alpha, 0.0, 3.0);
+    TestResult {
+        test: RandomnessTest::KolmogorovSmirnov,
+        statistic: d,
+        critical_value: lambda_crit / factor,
+        p_value,
+        df: None,
+        verdict: verdict(p_value, alpha),
+        classes: Vec::new(),
+    }
+}
+
+fn runs_up_down(u: &[f64], alpha: f64) -> TestResult {
+    let n = u.len() as f64;
+    // Signo de cada diferencia consecutiva; una racha es un bloque de signos iguales
+    let signs: Vec<bool> = u.windows(2).map(|w| w[1] > w[0]).collect();
+    let runs = 1 + signs.windows(2).filter(|w| w[0] != w[1]).count();
+    let mean = (2.0 * n - 1.0) / 3.0;
+    let var = (16.0 * n - 29.0) / 90.0;
+    z_result(RandomnessTest::RunsUpDown, (runs as f64 - mean) / var.sqrt(), alpha)
+}
+
+fn runs_above_below(u: &[f64], alpha: f64) -> Result<TestResult, Error> {
+    // Respecto a la media teórica 0.5 de la U(0,1)
+    let above: Vec<bool> = u.iter().map(|&x| x >= 0.5).collect();
+    let n1 = above.iter().filter(|&&a| a).count() as f64;
+    let n2 = above.len() as f64 - n1;
+    if n1 == 0.0 || n2 == 0.0 {
+        return Err(Error::Other("Todos los valores quedan del mismo lado de la media".into()));
+    }
+    let n = n1 + n2;
+    let runs = 1 + above.windows(2).filter(|w| w[0] != w[1]).count();
+    let mean = 2.0 * n1 * n2 / n + 0.5;
+    let var = 2.0 * n1 * n2 * (2.0 * n1 * n2 - n) / (n * n * (n - 1.0));
+    Ok(z_result(RandomnessTest::RunsAboveBelow, (runs as f64 - mean) / var.sqrt(), alpha))
+}
+
+/// Nombre de la mano según el patrón de repeticiones (ordenado de mayor a menor).
+fn poker_hand_name(pattern: &[usize]) -> String {
+    let name = match pattern {
+        [1, 1, 1, 1, 1] | [1, 1, 1, 1] | [1, 1, 1] => "todos diferentes",
+        [2, 1, 1, 1] | [2, 1, 1] | [2, 1] => "un par",
+        [2, 2, 1] | [2, 2] => "dos pares",
+        [3, 1, 1] | [3, 1] => "tercia",
+        [3, 2] => "full",
+        [3] => "tercia",
+        [4, 1] | [4] => "póker",
+        [5] => "quintilla",
+        _ => "",
+    };
+    if name.is_empty() {
+        pattern.iter().map(|c| c.to_string()).collect::<Vec<_>>().join("-")
+    } else {
+        name.to_string()
+    }
+}
+
+fn poker_pattern(mut hand: u32, digits: u32) -> Vec<usize> {
+    let mut counts = [0usize; 10];
+    for _ in 0..digits {
+        counts[(hand % 10) as usize] += 1;
+        hand /= 10;
+    }
+    let mut pattern: Vec<usize> = counts.into_iter().filter(|&c| c > 0).collect();
+    pattern.sort_unstable_by(|a, b| b.cmp(a));
+    pattern
+}
+
+fn poker(u: &[f64], digits: u32, alpha: f64) -> Result<TestResult, Error> {
+    if !(3..=5).contains(&digits) {
+        return Err(Error::Other("poker_digits debe ser 3, 4 o 5".into()));
+    }
+    let total = 10u32.pow(digits);
+    // Probabilidades exactas enumerando las 10^d manos posibles
+    let mut probs: HashMap<Vec<usize>, f64> = HashMap::new();
+    for hand in 0..total {
+        *probs.entry(poker_pattern(hand, digits)).or_insert(0.0) += 1.0 / total as f64;
+    }
+    let mut observed: HashMap<Vec<usize>, f64> = HashMap::new();
+    for &x in u {
+        // Se toman los primeros d decimales de cada número
+        let hand = ((x * total as f64) as u32).min(total - 1);
+        *observed.entry(poker_pattern(hand, digits)).or_insert(0.0) += 1.0;
+    }
+    let n = u.len() as f64;
+    let mut patterns: Vec<(Vec<usize>, f64)> = probs.into_iter().collect();
+    patterns.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    let classes = patterns
+        .into_iter()
+        .map(|(pattern, p)| ClassCount {
+            label: poker_hand_name(&pattern),
+            observed: observed.get(&pattern).copied().unwrap_or(0.0),
+            expected: n * p,
+        })
+        .collect();
+    chi_square_result(RandomnessTest::Poker, merge_small_tail(classes), 0, alpha)
+}
+
+fn gap(u: &[f64], interval: (f64, f64), max_gap: usize, alpha: f64) -> Result<TestResult, Error> {
+    let (a, b) = interval;
+    if !(0.0 <= a && a < b && b <= 1.0) {
+        return Err(Error::Other("gap_interval debe cumplir 0 <= a < b <= 1".into()));
+    }
+    if max_gap == 0 { return Err(Error::Other("gap_max debe ser >= 1".into())); }
+    let p = b - a;
+    // Longitud de cada hueco: números fuera de [a, b] entre dos éxitos consecutivos
+    let mut gaps = Vec::new();
+    let mut current: Option<usize> = None;
+    for &x in u {
+        if x >= a && x <= b {
+            if let Some(len) = current { gaps.push(len); }
+            current = Some(0);
+        } else if let Some(len) = current.as_mut() {
+            *len += 1;
+        }
+    }
+    if gaps.is_empty() {
+        return Err(Error::Other("No hay huecos completos en la secuencia".into()));
+    }
+    let total = gaps.len() as f64;
+    let mut classes: Vec<ClassCount> = (0..=max_gap)
+        .map(|k| {
+            let (label, prob) = if k < max_gap {
+                (k.to_string(), p * (1.0 - p).powi(k as i32))
+            } else {
+                (format!(">= {}", k), (1.0 - p).powi(k as i32))
+            };
+            ClassCount { label, observed: 0.0, expected: total * prob }
+        })
+        .collect();
+    for g in gaps {
+        classes[g.min(max_gap)].observed += 1.0;
+    }
+    chi_square_result(RandomnessTest::Gap, merge_small_tail(classes), 0, alpha)
+}
+
+fn serial(u: &[f64], k: usize, alpha: f64) -> Result<TestResult, Error> {
+    if k < 2 { return Err(Error::Other("serial_bins debe ser >= 2".into())); }
+    // Pares no traslapados (u1, u2), (u3, u4), ... en una rejilla k x k
+    let pairs = u.len() / 2;
+    let mut observed = vec![0.0; k * k];
+    for pair in u.chunks_exact(2) {
+        let i = ((pair[0] * k as f64) as usize).min(k - 1);
+        let j = ((pair[1] * k as f64) as usize).min(k - 1);
+        observed[i * k + j] += 1.0;
+    }
+    let expected = pairs as f64 / (k * k) as f64;
+    let classes = observed
+        .into_iter()
+        .enumerate()
+        .map(|(cell, o)| ClassCount { label: format!("({}, {})", cell / k + 1, cell % k + 1), observed: o, expected })
+        .collect();
+    chi_square_result(RandomnessTest::Serial, classes, 0, alpha)
+}
+
+fn autocorrelation(u: &[f64], lag: usize, start: usize, alpha: f64) -> Result<TestResult, Error> {
+    if lag == 0 || start == 0 {
+        return Err(Error::Other("lag e start deben ser >= 1".into()));
+    }
+    let n = u.len();
+    // M = mayor entero tal que i + (M+1) l <= N
+    if start + lag > n {
+        return Err(Error::Other("La secuencia es demasiado corta para ese lag".into()));
+    }
+    let m = (n - start) / lag - 1;
+    let sum: f64 = (0..=m)
+        .map(|k| u[start - 1 + k * lag] * u[start - 1 + (k + 1) * lag])
+        .sum();
+    let mf = m as f64;
+    let rho = sum / (mf + 1.0) - 0.25;
+    let sigma = (13.0 * mf + 7.0).sqrt() / (12.0 * (mf + 1.0));
+    Ok(z_result(RandomnessTest::Autocorrelation, rho / sigma, alpha))
+}
+
+pub fn run_tests(req: &RandomnessRequest) -> Result<RandomnessResponse, Error> {
+    let values: Vec<f64> = match &req.prng {
+        Some(gen) => prng::generate(gen)?.sequence.into_iter().map(|s| s.u).collect(),
+        None => req.values.clone(),
+    };
+    if values.len() < 3 { return Err(Error::NullOrEmptyInput); }
+    if values.iter().any(|x| !(0.0..=1.0).contains(x)) {
+        return Err(Error::Other("Los valores deben estar en [0, 1]".into()));
+    }
+    if !(req.alpha > 0.0 && req.alpha < 1.0) {
+        return Err(Error::Other("alpha debe estar en (0, 1)".into()));
+    }
+
+    let tests: &[RandomnessTest] = if req.tests.is_empty() { &ALL_TESTS } else { &req.tests };
+    let alpha = req.alpha;
+    let u = values.as_slice();
+    let mut results = Vec::with_capacity(tests.len());
+    for &test in tests {
+        let result = match test {
+            RandomnessTest::ChiSquare => chi_square_uniformity(u, req.bins, alpha)?,
+            RandomnessTest::KolmogorovSmirnov => kolmogorov_smirnov(u, alpha),
+            RandomnessTest::RunsUpDown => runs_up_down(u, alpha),
+            RandomnessTest::RunsAboveBelow => runs_above_below(u, alpha)?,
+            RandomnessTest::Poker => poker(u, req.poker_digits, alpha)?,
+            RandomnessTest::Gap => gap(u, req.gap_interval, req.gap_max, alpha)?,
+            RandomnessTest::Serial => serial(u, req.serial_bins, alpha)?,
+            RandomnessTest::Autocorrelation => autocorrelation(u, req.lag, req.start, alpha)?,
+        };
+        results.push(result);
+    }
+    let all_passed = results.iter().all(|r| r.verdict == Verdict::Pass);
+    Ok(RandomnessResponse { n: values.len(), alpha, results, all_passed })
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn randomness_tests_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, run_tests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() <= tol, "{} vs {}", a, b);
+    }
+
+    // Diez números con estadísticos calculados a mano
+    const U: [f64; 10] = [0.1234, 0.8765, 0.4321, 0.0551, 0.6602, 0.9137, 0.2828, 0.5449, 0.3777, 0.7912];
+
+    #[test]
+    fn reference_quantiles() {
+        assert_close(chi_square_critical(0.05, 9), 16.919, 1e-3);
+        assert_close(chi_square_critical(0.05, 1), 3.841, 1e-3);
+        assert_close(chi_square_sf(16.918977604620448, 9), 0.05, 1e-9);
+        assert_close(z_critical(0.05), 1.959964, 1e-6);
+        assert_close(bisect_decreasing(kolmogorov_sf, 0.05, 0.0, 3.0), 1.3581, 1e-4);
+    }
+
+    #[test]
+    fn chi_square_on_hand_sequence() {
+        // Cuartos: [2, 3, 2, 3] contra 2.5 esperados
+        let r = chi_square_uniformity(&U, Some(4), 0.05).unwrap();
+        assert_close(r.statistic, 0.4, 1e-12);
+        assert_eq!(r.df, Some(3));
+        assert_eq!(r.classes.iter().map(|c| c.observed).collect::<Vec<_>>(), vec![2.0, 3.0, 2.0, 3.0]);
+        assert!(r.verdict == Verdict::Pass);
+    }
+
+    #[test]
+    fn kolmogorov_smirnov_on_hand_sequence() {
+        // D- = 0.7912 - 0.7 en el octavo valor ordenado
+        let r = kolmogorov_smirnov(&U, 0.05);
+        assert_close(r.statistic, 0.0912, 1e-12);
+        let factor = 10f64.sqrt() + 0.12 + 0.11 / 10f64.sqrt();
+        assert_close(r.critical_value, 1.3581 / factor, 1e-4);
+        assert!(r.verdict == Verdict::Pass);
+    }
+
+    #[test]
+    fn runs_on_hand_sequence() {
+        // Signos + - - + + - + - + : 7 rachas
+        let r = runs_up_down(&U, 0.05);
+        assert_close(r.statistic, (7.0 - 19.0 / 3.0) / (131.0f64 / 90.0).sqrt(), 1e-12);
+        // B A B B A A B A B A respecto a 0.5: 8 rachas con n1 = n2 = 5
+        let r = runs_above_below(&U, 0.05).unwrap();
+        assert_close(r.statistic, (8.0 - 5.5) / (2000.0f64 / 900.0).sqrt(), 1e-12);
+        assert!(runs_above_below(&[0.1, 0.2, 0.3], 0.05).is_err());
+    }
+
+    #[test]
+    fn poker_on_hand_sequence() {
+        // Manos de 3 dígitos: 5 todos diferentes y 5 pares; la tercia (0.1) se agrupa con el par
+        let r = poker(&U, 3, 0.05).unwrap();
+        assert_eq!(r.classes.len(), 2);
+        assert_eq!(r.classes[0].label, "todos diferentes");
+        assert_eq!(r.classes[1].label, "un par + tercia");
+        assert_close(r.classes[0].expected, 7.2, 1e-9);
+        assert_close(r.classes[1].expected, 2.8, 1e-9);
+        assert_close(r.statistic, 4.84 / 7.2 + 4.84 / 2.8, 1e-9);
+        assert_eq!(r.df, Some(1));
+        assert_eq!(poker_pattern(55, 3), vec![2, 1]);
+        assert_eq!(poker_hand_name(&[3, 2]), "full");
+    }
+}
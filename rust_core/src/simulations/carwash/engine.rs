@@ -1,50 +1,16 @@
 use rand::prelude::*;
-use rand_distr::{Exp, Normal, Uniform, Distribution};
+use rand_distr::{Exp, Distribution};
 use crate::errors::Error;
 use super::models::*;
 
-enum PreparedDist {
-    Normal(Normal<f64>),
-    Exponential(Exp<f64>),
-    Uniform(Uniform<f64>),
-    None,
-}
-
-impl PreparedDist {
-    #[inline(always)]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
-        match self {
-            PreparedDist::Normal(d) => d.sample(rng).max(0.0),
-            PreparedDist::Exponential(d) => d.sample(rng),
-            PreparedDist::Uniform(d) => d.sample(rng),
-            PreparedDist::None => 0.0,
-        }
-    }
-}
-
 pub fn execute_simulation(config: SimConfig) -> Result<SimulationResponse, Error> {
     if config.hours <= 0 { return Err(Error::NullOrEmptyInput); }
 
     let mut prepared_stages = Vec::with_capacity(config.stages.len());
     for s in &config.stages {
-        let dist = match s.dist_type.as_str() {
-            "normal" => {
-                if s.p2 < 0.0 { return Err(Error::Other(format!("Varianza negativa en {}", s.name))); }
-                let std = s.p2.sqrt();
-                PreparedDist::Normal(Normal::new(s.p1, std).unwrap())
-            },
-            "exponential" => {
-                if s.p1 <= 0.0 { return Err(Error::Other(format!("Beta <= 0 en {}", s.name))); }
-                let lambda = 1.0 / s.p1;
-                PreparedDist::Exponential(Exp::new(lambda).unwrap())
-            },
-            "uniform" => {
-                if s.p1 >= s.p2 { return Err(Error::Other(format!("Min >= Max en {}", s.name))); }
-                PreparedDist::Uniform(Uniform::new_inclusive(s.p1, s.p2))
-            },
-            _ => PreparedDist::None,
-        };
-        prepared_stages.push(dist);
+        let dist = s.service_dist()
+            .map_err(|msg| Error::Other(format!("{} en {}", msg, s.name)))?;
+        prepared_stages.push(dist.sampler());
     }
 
     let mut rng = thread_rng();
@@ -130,7 +96,7 @@ pub fn execute_simulation(config: SimConfig) -> Result<SimulationResponse, Error
                         total_wait += stage_start - current_stage_time;
                     }
                     
-                    let duration = dist.sample(&mut rng);
+                    let duration = dist.sample(&mut rng).max(0.0); // Sin tiempos negativos (colas de la normal)
                     let stage_end = stage_start + duration;
                     
                     if stage_end > hour_end { finishes_in_same_hour = false; }
@@ -1,13 +1,33 @@
 use serde::{Deserialize, Serialize};
+use crate::probabilities::distribution::{Dist, DistInput};
 
 #[derive(Deserialize)]
 pub struct StageConfig {
     pub name: String,
-    pub dist_type: String,
+    // Tiempo de servicio: `distribution` ({"type": "normal", "mean": 5, "std": 1}) o la forma
+    // antigua dist_type + p1/p2 (normal: media y varianza)
+    #[serde(default)]
+    pub distribution: Option<Dist>,
+    #[serde(default)]
+    pub dist_type: Option<String>,
+    #[serde(default)]
     pub p1: f64,
+    #[serde(default)]
     pub p2: f64,
 }
 
+impl StageConfig {
+    pub fn service_dist(&self) -> Result<Dist, String> {
+        DistInput {
+            distribution: self.distribution.clone(),
+            dist_type: self.dist_type.clone(),
+            param1: self.p1,
+            param2: self.p2,
+            param3: 0.0,
+        }.resolve()
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SimConfig {
     pub hours: i32,
@@ -0,0 +1,303 @@
+use rand::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use crate::errors::Error;
+use crate::aggregation::boxplot::percentile_sorted;
+use crate::simulations::montecarlo::engine::{build_sampler, FastDist};
+use super::models::*;
+
+/// Q del newsvendor por fractil crítico Cu / (Cu + Co), estimado con muestras de la demanda.
+fn critical_fractile_quantity(demand: &FastDist, costs: &CostConfig, rng: &mut ChaCha20Rng) -> Result<f64, Error> {
+    let cu = costs.shortage_per_unit;
+    let co = costs.holding_per_unit_day;
+    if cu + co <= 0.0 {
+        return Err(Error::Other("Newsvendor requiere costo de faltante o de sobrante > 0".into()));
+    }
+    let mut draws: Vec<f64> = (0..100_000).map(|_| demand.sample(rng).max(0.0)).collect();
+    draws.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let q = percentile_sorted(&draws, cu / (cu + co));
+    if !q.is_finite() {
+        return Err(Error::Other("La demanda produce un Q no finito en el fractil crítico".into()));
+    }
+    Ok(q)
+}
+
+fn summarize(values: &[f64]) -> MetricSummary {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let std_dev = if n > 1.0 {
+        (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let half = 1.96 * std_dev / n.sqrt();
+    MetricSummary { mean, std_dev, ci_low: mean - half, ci_high: mean + half }
+}
+
+pub fn execute(config: InventoryConfig) -> Result<InventoryResponse, Error> {
+    if config.days == 0 || config.replications == 0 { return Err(Error::NullOrEmptyInput); }
+
+    // 1. Validación
+    let demand = build_sampler("demanda", &config.demand, 1.0)?;
+    let lead_time = build_sampler("lead time", &config.lead_time, 1.0)?;
+    let c = &config.costs;
+    if c.holding_per_unit_day < 0.0 || c.shortage_per_unit < 0.0 || c.order_fixed < 0.0 || c.unit_cost < 0.0 {
+        return Err(Error::Other("Los costos no pueden ser negativos".into()));
+    }
+    match config.policy {
+        OrderPolicy::SS { reorder_point, order_up_to } => {
+            if order_up_to <= reorder_point {
+                return Err(Error::Other(format!("S ({}) debe ser mayor que s ({})", order_up_to, reorder_point)));
+            }
+        },
+        OrderPolicy::RQ { order_quantity, .. } => {
+            if order_quantity <= 0.0 { return Err(Error::Other("Q debe ser > 0".into())); }
+        },
+        OrderPolicy::Newsvendor { order_quantity: Some(q) } if !(q >= 0.0 && q.is_finite()) => {
+            return Err(Error::Other(format!("Q debe ser finito y no negativo (se recibió {})", q)));
+        },
+        OrderPolicy::Newsvendor { .. } => {},
+    }
+
+    let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+
+    let order_quantity = match config.policy {
+        OrderPolicy::Newsvendor { order_quantity: Some(q) } => Some(q),
+        OrderPolicy::Newsvendor { order_quantity: None } => {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            rng.set_stream(u64::MAX); // Stream reservado, no se cruza con las réplicas
+            Some(critical_fractile_quantity(&demand, c, &mut rng)?)
+        },
+        _ => None,
+    };
+
+    // 2. Réplicas (cada una con su propio stream ChaCha)
+    let mut replications = Vec::with_capacity(config.replications);
+    let mut daily_trace = Vec::new();
+    for rep in 0..config.replications {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        rng.set_stream(rep as u64);
+        let trace = if rep == 0 { Some(&mut daily_trace) } else { None };
+        let result = match order_quantity {
+            Some(q) => run_newsvendor(&config, &demand, q, &mut rng, trace),
+            None => run_reorder_policy(&config, &demand, &lead_time, &mut rng, trace),
+        };
+        replications.push(ReplicationResult { replication: rep + 1, ..result });
+    }
+
+    // 3. Resumen entre réplicas
+    let collect = |f: fn(&ReplicationResult) -> f64| -> Vec<f64> { replications.iter().map(f).collect() };
+    Ok(InventoryResponse {
+        seed,
+        order_quantity,
+        service_level: summarize(&collect(|r| r.service_level)),
+        fill_rate: summarize(&collect(|r| r.fill_rate)),
+        average_inventory: summarize(&collect(|r| r.average_inventory)),
+        total_cost: summarize(&collect(|r| r.total_cost)),
+        replications,
+        daily_trace,
+    })
+}
+
+/// Políticas de revisión continua (s, S) y (R, Q).
+fn run_reorder_policy(
+    config: &InventoryConfig,
+    demand: &FastDist,
+    lead_time: &FastDist,
+    rng: &mut ChaCha20Rng,
+    mut trace: Option<&mut Vec<DayRecord>>,
+) -> ReplicationResult {
+    let c = &config.costs;
+    let mut on_hand = config.initial_inventory;
+    let mut pipeline: Vec<(usize, f64)> = Vec::new(); // (día de llegada, cantidad)
+
+    let mut total_demand = 0.0;
+    let mut total_sold = 0.0;
+    let mut inventory_sum = 0.0;
+    let mut holding_cost = 0.0;
+    let mut shortage_cost = 0.0;
+    let mut ordering_cost = 0.0;
+    let mut orders_placed = 0;
+    let mut stockout_days = 0;
+
+    for day in 0..config.days {
+        // a) Recepción de pedidos que llegan hoy
+        let mut received = 0.0;
+        pipeline.retain(|&(arrival, qty)| {
+            if arrival <= day { received += qty; false } else { true }
+        });
+        on_hand += received;
+
+        // b) Demanda (con backorders, primero se atiende lo pendiente)
+        let d = demand.sample(rng).max(0.0);
+        let available = on_hand.max(0.0);
+        let sold = d.min(available);
+        let shortage = d - sold;
+        on_hand = if config.backorders { on_hand - d } else { on_hand - sold };
+
+        total_demand += d;
+        total_sold += sold;
+        if shortage > 0.0 { stockout_days += 1; }
+        shortage_cost += shortage * c.shortage_per_unit;
+        holding_cost += on_hand.max(0.0) * c.holding_per_unit_day;
+        inventory_sum += on_hand.max(0.0);
+
+        // c) Revisión: posición = disponible + en tránsito (- pendientes)
+        let in_transit: f64 = pipeline.iter().map(|&(_, q)| q).sum();
+        let position = on_hand + in_transit;
+        let order = match config.policy {
+            OrderPolicy::SS { reorder_point, order_up_to } if position <= reorder_point => order_up_to - position,
+            OrderPolicy::RQ { reorder_point, order_quantity } if position <= reorder_point => {
+                let multiples = ((reorder_point - position) / order_quantity).floor() + 1.0;
+                multiples * order_quantity
+            },
+            _ => 0.0,
+        };
+        if order > 0.0 {
+            // Un pedido con lead time L llega al inicio del día t + L (mínimo el día siguiente);
+            // más allá del horizonte ya no llega, así que L se acota a `days` para no desbordar
+            let lt = lead_time.sample(rng).round().max(1.0).min(config.days as f64) as usize;
+            pipeline.push((day + lt, order));
+            orders_placed += 1;
+            ordering_cost += c.order_fixed + order * c.unit_cost;
+        }
+
+        if let Some(t) = trace.as_deref_mut() {
+            t.push(DayRecord {
+                day: day + 1, received, demand: d, sold, shortage, on_hand,
+                inventory_position: position + order, order_placed: order,
+            });
+        }
+    }
+
+    let days = config.days as f64;
+    ReplicationResult {
+        replication: 0,
+        service_level: 1.0 - stockout_days as f64 / days,
+        fill_rate: if total_demand > 0.0 { total_sold / total_demand } else { 1.0 },
+        average_inventory: inventory_sum / days,
+        holding_cost,
+        shortage_cost,
+        ordering_cost,
+        total_cost: holding_cost + shortage_cost + ordering_cost,
+        orders_placed,
+        stockout_days,
+    }
+}
+
+/// Newsvendor: cada día se reciben Q unidades; el sobrante se pierde al cierre.
+fn run_newsvendor(
+    config: &InventoryConfig,
+    demand: &FastDist,
+    q: f64,
+    rng: &mut ChaCha20Rng,
+    mut trace: Option<&mut Vec<DayRecord>>,
+) -> ReplicationResult {
+    let c = &config.costs;
+    let mut total_demand = 0.0;
+    let mut total_sold = 0.0;
+    let mut inventory_sum = 0.0;
+    let mut holding_cost = 0.0;
+    let mut shortage_cost = 0.0;
+    let mut ordering_cost = 0.0;
+    let mut stockout_days = 0;
+
+    for day in 0..config.days {
+        let d = demand.sample(rng).max(0.0);
+        let sold = d.min(q);
+        let shortage = d - sold;
+        let leftover = q - sold;
+
+        total_demand += d;
+        total_sold += sold;
+        if shortage > 0.0 { stockout_days += 1; }
+        inventory_sum += leftover;
+        holding_cost += leftover * c.holding_per_unit_day;
+        shortage_cost += shortage * c.shortage_per_unit;
+        ordering_cost += c.order_fixed + q * c.unit_cost;
+
+        if let Some(t) = trace.as_deref_mut() {
+            t.push(DayRecord {
+                day: day + 1, received: q, demand: d, sold, shortage, on_hand: leftover,
+                inventory_position: leftover, order_placed: q,
+            });
+        }
+    }
+
+    let days = config.days as f64;
+    ReplicationResult {
+        replication: 0,
+        service_level: 1.0 - stockout_days as f64 / days,
+        fill_rate: if total_demand > 0.0 { total_sold / total_demand } else { 1.0 },
+        average_inventory: inventory_sum / days,
+        holding_cost,
+        shortage_cost,
+        ordering_cost,
+        total_cost: holding_cost + shortage_cost + ordering_cost,
+        orders_placed: config.days,
+        stockout_days,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Demanda de 10 por día y lead time de 3 días, ambos constantes
+    fn config(lead_time: f64) -> InventoryConfig {
+        serde_json::from_str(&format!(
+            r#"{{
+                "days": 10, "replications": 2, "initial_inventory": 30, "seed": 1,
+                "demand": {{"type": "empirical", "data": [10]}},
+                "lead_time": {{"type": "empirical", "data": [{}]}},
+                "policy": {{"policy_type": "SS", "params": {{"reorder_point": 10, "order_up_to": 40}}}},
+                "costs": {{"holding_per_unit_day": 1, "shortage_per_unit": 5, "order_fixed": 20, "unit_cost": 2}}
+            }}"#,
+            lead_time
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn deterministic_ss_policy() {
+        // Pide 30 los días 2, 6 y 10; llegan los días 5 y 9, así que faltan 10 unidades los días 4 y 8
+        let r = execute(config(3.0)).unwrap();
+        for rep in &r.replications {
+            assert_eq!(rep.orders_placed, 3);
+            assert_eq!(rep.stockout_days, 2);
+            assert_eq!(rep.fill_rate, 0.8);
+            assert_eq!(rep.service_level, 0.8);
+            assert_eq!(rep.average_inventory, 9.0);
+            assert_eq!(rep.holding_cost, 90.0);
+            assert_eq!(rep.shortage_cost, 100.0);
+            assert_eq!(rep.ordering_cost, 240.0);
+            assert_eq!(rep.total_cost, 430.0);
+        }
+        assert_eq!(r.total_cost.std_dev, 0.0);
+        let received: Vec<f64> = r.daily_trace.iter().map(|d| d.received).collect();
+        assert_eq!(received, vec![0.0, 0.0, 0.0, 0.0, 30.0, 0.0, 0.0, 0.0, 30.0, 0.0]);
+    }
+
+    #[test]
+    fn huge_lead_time_does_not_overflow() {
+        // El pedido del día 2 nunca llega dentro del horizonte
+        let r = execute(config(1e300)).unwrap();
+        let rep = &r.replications[0];
+        assert_eq!(rep.orders_placed, 1);
+        assert_eq!(rep.fill_rate, 0.3);
+        assert!(r.daily_trace.iter().all(|d| d.received == 0.0));
+    }
+
+    #[test]
+    fn rejects_non_finite_newsvendor_quantity() {
+        for q in [f64::INFINITY, f64::NAN, -1.0] {
+            let mut c = config(1.0);
+            c.policy = OrderPolicy::Newsvendor { order_quantity: Some(q) };
+            assert!(execute(c).is_err());
+        }
+        let mut c = config(1.0);
+        c.policy = OrderPolicy::Newsvendor { order_quantity: Some(12.0) };
+        let r = execute(c).unwrap();
+        assert_eq!(r.order_quantity, Some(12.0));
+        assert_eq!(r.replications[0].holding_cost, 20.0);
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::simulations::montecarlo::models::DistType;
+
+#[derive(Deserialize)]
+#[serde(tag = "policy_type", content = "params")]
+pub enum OrderPolicy {
+    // (s, S): si la posición de inventario <= s, pedir hasta S
+    SS { reorder_point: f64, order_up_to: f64 },
+    // (R, Q): si la posición de inventario <= R, pedir múltiplos de Q hasta superar R
+    RQ { reorder_point: f64, order_quantity: f64 },
+    // Vendedor de periódicos: cada día se empieza con Q unidades y el sobrante se pierde.
+    // Si no se da Q se usa el fractil crítico Cu / (Cu + Co).
+    Newsvendor { #[serde(default)] order_quantity: Option<f64> },
+}
+
+#[derive(Deserialize)]
+pub struct CostConfig {
+    pub holding_per_unit_day: f64, // En newsvendor: costo por unidad sobrante (Co)
+    pub shortage_per_unit: f64,    // Costo por unidad faltante (Cu)
+    pub order_fixed: f64,          // Costo fijo por pedido
+    #[serde(default)]
+    pub unit_cost: f64,            // Costo de compra por unidad
+}
+
+#[derive(Deserialize)]
+pub struct InventoryConfig {
+    pub days: usize,
+    pub replications: usize,
+    pub initial_inventory: f64,
+    pub demand: DistType,    // Demanda diaria (valores negativos se truncan a 0)
+    pub lead_time: DistType, // Días; se redondea al entero más cercano
+    pub policy: OrderPolicy,
+    pub costs: CostConfig,
+    #[serde(default)]
+    pub backorders: bool, // false = ventas perdidas
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DayRecord {
+    pub day: usize,
+    pub received: f64,
+    pub demand: f64,
+    pub sold: f64,
+    pub shortage: f64,
+    pub on_hand: f64,            // Al cierre (negativo = pendientes con backorders)
+    pub inventory_position: f64, // on_hand + en tránsito
+    pub order_placed: f64,
+}
+
+#[derive(Serialize)]
+pub struct ReplicationResult {
+    pub replication: usize,
+    pub service_level: f64,     // Fracción de días sin faltantes
+    pub fill_rate: f64,         // Demanda servida desde stock / demanda total
+    pub average_inventory: f64, // Inventario promedio al cierre
+    pub holding_cost: f64,
+    pub shortage_cost: f64,
+    pub ordering_cost: f64,
+    pub total_cost: f64,
+    pub orders_placed: usize,
+    pub stockout_days: usize,
+}
+
+#[derive(Serialize)]
+pub struct MetricSummary {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub ci_low: f64,  // IC 95% de la media entre réplicas
+    pub ci_high: f64,
+}
+
+#[derive(Serialize)]
+pub struct InventoryResponse {
+    pub seed: u64,
+    pub order_quantity: Option<f64>, // Q usado por newsvendor
+    pub service_level: MetricSummary,
+    pub fill_rate: MetricSummary,
+    pub average_inventory: MetricSummary,
+    pub total_cost: MetricSummary,
+    pub replications: Vec<ReplicationResult>,
+    pub daily_trace: Vec<DayRecord>, // Primera réplica, para graficar
+}
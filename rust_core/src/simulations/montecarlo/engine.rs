@@ -1,98 +1,342 @@
 use rand::prelude::*;
-use rand_distr::{Exp, Normal, Uniform, Distribution};
+use rand_distr::{Distribution as _, Normal, Uniform};
 use crate::errors::Error;
+use rand_chacha::ChaCha20Rng;
 use super::models::*;
+use super::sensitivity;
+use crate::aggregation::boxplot::percentile_sorted;
+use crate::probabilities::distribution::{Dist, Distribution, Sampler};
+use crate::sampling::quasi::QuasiSequence;
 
-enum FastDist {
-    Norm(Normal<f64>, f64), 
-    Expon(Exp<f64>, f64),
-    Unif(Uniform<f64>, f64),
+pub(crate) enum FastDist {
+    Param(Sampler, f64),
+    Boot(Uniform<usize>, Vec<f64>, f64),
+    Kde(Uniform<usize>, Vec<f64>, Normal<f64>, f64),
 }
 
 impl FastDist {
     #[inline(always)]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
         match self {
-            FastDist::Norm(d, m) => d.sample(rng) * m,
-            FastDist::Expon(d, m) => d.sample(rng) * m,
-            FastDist::Unif(d, m) => d.sample(rng) * m,
+            FastDist::Param(d, m) => d.sample(rng) * m,
+            FastDist::Boot(idx, data, m) => data[idx.sample(rng)] * m,
+            FastDist::Kde(idx, data, noise, m) => (data[idx.sample(rng)] + noise.sample(rng)) * m,
         }
     }
 }
 
-pub fn execute(config: MonteCarloConfig) -> Result<MonteCarloResponse, Error> {
-    if config.n_simulations == 0 { return Err(Error::NullOrEmptyInput); }
+fn invalid(name: &str, msg: &str) -> Error {
+    Error::Other(format!("{} en {}", msg, name))
+}
 
-    // 1. Preparación
-    let mut distributions = Vec::with_capacity(config.variables.len());
-    
-    for v in &config.variables {
-        let dist = match v.distribution {
-            DistType::Normal { mean, variance } => {
-                if variance < 0.0 { return Err(Error::Other(format!("Varianza negativa en {}", v.name))); }
-                let std = variance.sqrt(); // Calculamos std desde varianza
-                FastDist::Norm(Normal::new(mean, std).unwrap(), v.multiplier)
-            },
-            DistType::Exponential { beta } => {
-                if beta <= 0.0 { return Err(Error::Other(format!("Beta <= 0 en {}", v.name))); }
-                FastDist::Expon(Exp::new(1.0 / beta).unwrap(), v.multiplier)
+/// Valida los parámetros y construye el muestreador de una variable.
+fn prepare_distribution(v: &VariableConfig) -> Result<FastDist, Error> {
+    build_sampler(&v.name, &v.distribution, v.multiplier)
+}
+
+pub(crate) fn build_sampler(name: &str, distribution: &DistType, m: f64) -> Result<FastDist, Error> {
+    let (data, method, bandwidth) = match *distribution {
+        // Las paramétricas (incluida Fitted) llegan validadas desde probabilities::distribution
+        DistType::Param(ref dist) => return Ok(FastDist::Param(dist.sampler(), m)),
+        DistType::Empirical { ref data, method, bandwidth } => (data, method, bandwidth),
+    };
+    if data.is_empty() { return Err(invalid(name, "Los datos empíricos están vacíos")); }
+    if data.iter().any(|x| !x.is_finite()) {
+        return Err(invalid(name, "Los datos empíricos contienen valores no finitos"));
+    }
+    let idx = Uniform::new(0, data.len());
+    let dist = match method {
+        EmpiricalMethod::Bootstrap => FastDist::Boot(idx, data.clone(), m),
+        EmpiricalMethod::Kernel => {
+            let h = match bandwidth {
+                Some(h) if !(h > 0.0 && h.is_finite()) => {
+                    return Err(invalid(name, &format!("Ancho de banda {} debe ser > 0", h)));
+                },
+                Some(h) => h,
+                None => silverman_bandwidth(data),
+            };
+            FastDist::Kde(idx, data.clone(), Normal::new(0.0, h).unwrap(), m)
+        },
+    };
+    Ok(dist)
+}
+
+/// Regla de Silverman: h = 0.9 * min(s, IQR/1.34) * n^(-1/5)
+fn silverman_bandwidth(data: &[f64]) -> f64 {
+    let n = data.len() as f64;
+    let mean = data.iter().sum::<f64>() / n;
+    let std = if n > 1.0 {
+        (data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let iqr = percentile_sorted(&sorted, 0.75) - percentile_sorted(&sorted, 0.25);
+    let spread = if iqr > 0.0 { std.min(iqr / 1.34) } else { std };
+    0.9 * spread * n.powf(-0.2)
+}
+
+// Tamaño fijo de bloque: la partición no depende del número de hilos, así cada
+// bloque usa siempre el mismo stream ChaCha y el resultado es reproducible.
+const BLOCK_SIZE: usize = 8192;
+
+// Fuente cuasi-aleatoria: el punto i+1 de la secuencia alimenta la iteración i
+struct QuasiPlan {
+    sequence: QuasiSequence,
+    inverses: Vec<(Dist, f64)>, // Distribución resuelta y multiplicador por variable
+}
+
+struct BlockPlan<'a> {
+    config: &'a MonteCarloConfig,
+    distributions: &'a [FastDist],
+    quasi: Option<&'a QuasiPlan>,
+    seed: u64,
+    preview_size: usize,
+    keep_columns: bool,
+    keep_totals: bool,
+}
+
+/// Acumuladores parciales de un bloque de iteraciones.
+struct BlockAccumulator {
+    sum_x: f64,
+    sum_x2: f64,
+    min_val: f64,
+    max_val: f64,
+    success_counter: usize,
+    threshold_counters: Vec<usize>,
+    preview: Vec<IterationDetail>,
+    columns: Vec<Vec<f64>>,
+    totals: Vec<f64>,
+    period_states: Vec<Vec<f64>>, // [periodo][iteración], solo multi-periodo
+    ruin_first: Vec<usize>,       // Iteraciones cuya primera ruina ocurrió en el periodo t
+}
+
+impl BlockAccumulator {
+    fn new(plan: &BlockPlan) -> Self {
+        BlockAccumulator {
+            sum_x: 0.0,
+            sum_x2: 0.0,
+            min_val: f64::MAX,
+            max_val: f64::MIN,
+            success_counter: 0,
+            threshold_counters: vec![0; plan.config.thresholds.len()],
+            preview: Vec::new(),
+            columns: if plan.keep_columns { vec![Vec::new(); plan.distributions.len()] } else { Vec::new() },
+            totals: Vec::new(),
+            period_states: vec![Vec::new(); plan.n_periods()],
+            ruin_first: vec![0; plan.n_periods()],
+        }
+    }
+
+    /// Fusiona el bloque siguiente (el orden de llamada debe ser el orden de bloque).
+    fn merge(&mut self, other: BlockAccumulator) {
+        self.sum_x += other.sum_x;
+        self.sum_x2 += other.sum_x2;
+        self.min_val = self.min_val.min(other.min_val);
+        self.max_val = self.max_val.max(other.max_val);
+        self.success_counter += other.success_counter;
+        for (a, b) in self.threshold_counters.iter_mut().zip(other.threshold_counters) { *a += b; }
+        self.preview.extend(other.preview);
+        for (a, b) in self.columns.iter_mut().zip(other.columns) { a.extend(b); }
+        self.totals.extend(other.totals);
+        for (a, b) in self.period_states.iter_mut().zip(other.period_states) { a.extend(b); }
+        for (a, b) in self.ruin_first.iter_mut().zip(other.ruin_first) { *a += b; }
+    }
+}
+
+impl BlockPlan<'_> {
+    fn n_periods(&self) -> usize {
+        self.config.multi_period.as_ref().map_or(0, |mp| mp.n_periods)
+    }
+
+    /// Valor de la variable `var`; con fuente cuasi-aleatoria usa la coordenada `dim` del punto.
+    #[inline(always)]
+    fn draw(&self, rng: &mut ChaCha20Rng, point: &[f64], dim: usize, var: usize) -> f64 {
+        match self.quasi {
+            Some(q) => {
+                let (dist, m) = &q.inverses[var];
+                dist.quantile(point[dim]) * m
             },
-            DistType::Uniform { min, max } => {
-                if min >= max { return Err(Error::Other(format!("Min >= Max en {}", v.name))); }
-                FastDist::Unif(Uniform::new_inclusive(min, max), v.multiplier)
-            }
-        };
-        distributions.push(dist);
+            None => self.distributions[var].sample(rng),
+        }
     }
+}
 
-    // 2. Ejecución
-    let mut rng = thread_rng();
-    let mut sum_x = 0.0;
-    let mut sum_x2 = 0.0;
-    let mut min_val = f64::MAX;
-    let mut max_val = f64::MIN;
-    let mut success_counter = 0;
-    
-    let preview_size = 50.min(config.n_simulations);
-    // Corrección: Vector de objetos IterationDetail
-    let mut preview: Vec<IterationDetail> = Vec::with_capacity(preview_size);
+fn run_block(plan: &BlockPlan, block: usize) -> BlockAccumulator {
+    let config = plan.config;
+    let start = block * BLOCK_SIZE;
+    let end = (start + BLOCK_SIZE).min(config.n_simulations);
 
-    for i in 0..config.n_simulations {
+    let mut rng = ChaCha20Rng::seed_from_u64(plan.seed);
+    rng.set_stream(block as u64);
+
+    let mut acc = BlockAccumulator::new(plan);
+    if plan.keep_columns {
+        for col in acc.columns.iter_mut() { col.reserve(end - start); }
+    }
+    if plan.keep_totals { acc.totals.reserve(end - start); }
+    for states in acc.period_states.iter_mut() { states.reserve(end - start); }
+    let n_vars = plan.distributions.len();
+    let mut point = vec![0.0; plan.quasi.map_or(0, |q| q.sequence.dims())];
+
+    for i in start..end {
+        if let Some(q) = plan.quasi { q.sequence.point(i as u64 + 1, &mut point); }
         let mut total_val = 0.0;
         // Guardamos los valores individuales de esta iteración
-        let mut current_vars = Vec::with_capacity(distributions.len());
+        let mut current_vars = Vec::with_capacity(plan.distributions.len());
 
-        for d in &distributions {
-            let val = d.sample(&mut rng);
-            current_vars.push(val); 
-            total_val += val;
+        if let Some(mp) = &config.multi_period {
+            // Cada variable guarda su aporte acumulado en todos los periodos
+            current_vars.resize(plan.distributions.len(), 0.0);
+            let mut state = mp.initial_state;
+            let mut ruined = false;
+            for t in 0..mp.n_periods {
+                for (j, acc_val) in current_vars.iter_mut().enumerate() {
+                    let val = plan.draw(&mut rng, &point, t * n_vars + j, j);
+                    *acc_val += val;
+                    state += val;
+                }
+                if let Some(lo) = mp.min_state { state = state.max(lo); }
+                if let Some(hi) = mp.max_state { state = state.min(hi); }
+                acc.period_states[t].push(state);
+
+                if let Some(ruin) = &mp.ruin {
+                    if !ruined && ruin.operator.eval(state, ruin.threshold) {
+                        ruined = true;
+                        acc.ruin_first[t] += 1;
+                    }
+                }
+            }
+            total_val = state;
+        } else {
+            for j in 0..n_vars {
+                let val = plan.draw(&mut rng, &point, j, j);
+                current_vars.push(val); 
+                total_val += val;
+            }
+        }
+
+        if plan.keep_columns {
+            for (col, &val) in acc.columns.iter_mut().zip(&current_vars) { col.push(val); }
         }
+        if plan.keep_totals { acc.totals.push(total_val); }
 
-        sum_x += total_val;
-        sum_x2 += total_val * total_val;
-        if total_val < min_val { min_val = total_val; }
-        if total_val > max_val { max_val = total_val; }
+        acc.sum_x += total_val;
+        acc.sum_x2 += total_val * total_val;
+        if total_val < acc.min_val { acc.min_val = total_val; }
+        if total_val > acc.max_val { acc.max_val = total_val; }
 
         // Guardamos el objeto completo si es parte del preview
-        if i < preview_size { 
-            preview.push(IterationDetail {
+        if i < plan.preview_size { 
+            acc.preview.push(IterationDetail {
                 variables: current_vars,
                 total: total_val
             }); 
         }
 
         // Lógica de Probabilidad
-        if let AnalysisMode::Probability { threshold, ref operator, .. } = config.analysis {
-            let passed = match operator.as_str() {
-                "<" => total_val < threshold,
-                "<=" => total_val <= threshold,
-                ">" => total_val > threshold,
-                ">=" => total_val >= threshold,
-                _ => false, 
-            };
-            if passed { success_counter += 1; }
+        let passed = match config.analysis {
+            AnalysisMode::Aggregation => false,
+            AnalysisMode::Probability { threshold, operator, .. } => operator.eval(total_val, threshold),
+            AnalysisMode::Between { low, high, .. } => total_val >= low && total_val <= high,
+        };
+        if passed { acc.success_counter += 1; }
+
+        for (counter, check) in acc.threshold_counters.iter_mut().zip(&config.thresholds) {
+            if check.operator.eval(total_val, check.threshold) { *counter += 1; }
         }
     }
+    acc
+}
+
+pub fn execute(config: MonteCarloConfig) -> Result<MonteCarloResponse, Error> {
+    if config.n_simulations == 0 { return Err(Error::NullOrEmptyInput); }
+
+    // 1. Preparación
+    let mut distributions = Vec::with_capacity(config.variables.len());
+    
+    for v in &config.variables {
+        distributions.push(prepare_distribution(v)?);
+    }
+
+    if let AnalysisMode::Between { low, high, .. } = config.analysis {
+        if low > high { return Err(Error::Other(format!("Rango inválido: low ({}) > high ({})", low, high))); }
+    }
+    if let Some(mp) = &config.multi_period {
+        if mp.n_periods == 0 { return Err(Error::Other("n_periods debe ser >= 1".into())); }
+        if let (Some(lo), Some(hi)) = (mp.min_state, mp.max_state) {
+            if lo > hi { return Err(Error::Other(format!("min_state ({}) > max_state ({})", lo, hi))); }
+        }
+        if let Some(p) = mp.percentiles.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(Error::Other(format!("Percentil {} fuera de [0, 1]", p)));
+        }
+    }
+    for &level in &config.risk_levels {
+        if !(level > 0.0 && level < 1.0) {
+            return Err(Error::Other(format!("Nivel de riesgo {} fuera de (0, 1)", level)));
+        }
+    }
+
+    // 2. Ejecución paralela por bloques
+    let seed = config.seed.unwrap_or_else(|| thread_rng().gen());
+    let quasi = match &config.quasi {
+        Some(spec) => {
+            let inverses = config.variables.iter().map(|v| {
+                let DistType::Param(ref dist) = v.distribution else {
+                    return Err(invalid(&v.name, "Distribución sin transformada inversa para la fuente cuasi-aleatoria"));
+                };
+                Ok((dist.clone(), v.multiplier))
+            }).collect::<Result<Vec<_>, Error>>()?;
+            let n_periods = config.multi_period.as_ref().map_or(1, |mp| mp.n_periods);
+            let sequence = QuasiSequence::new(spec, config.variables.len() * n_periods, seed)?;
+            Some(QuasiPlan { sequence, inverses })
+        },
+        None => None,
+    };
+    let n_blocks = config.n_simulations.div_ceil(BLOCK_SIZE);
+    let n_threads = config.threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|t| t.get()).unwrap_or(1))
+        .clamp(1, n_blocks);
+
+    // Columnas completas solo si se pidió sensibilidad (memoria O(n * vars))
+    let keep_columns = config.sensitivity;
+    let keep_totals = keep_columns || !config.risk_levels.is_empty();
+    let plan = BlockPlan {
+        config: &config,
+        distributions: &distributions,
+        quasi: quasi.as_ref(),
+        seed,
+        preview_size: 50.min(config.n_simulations),
+        keep_columns,
+        keep_totals,
+    };
+
+    let mut blocks: Vec<Option<BlockAccumulator>> = (0..n_blocks).map(|_| None).collect();
+    std::thread::scope(|s| -> Result<(), Error> {
+        let plan = &plan;
+        let handles: Vec<_> = (0..n_threads).map(|t| {
+            s.spawn(move || {
+                (t..n_blocks).step_by(n_threads)
+                    .map(|b| (b, run_block(plan, b)))
+                    .collect::<Vec<_>>()
+            })
+        }).collect();
+        for h in handles {
+            let done = h.join().map_err(|_| Error::Other("Falló un hilo de la simulación".into()))?;
+            for (b, acc) in done { blocks[b] = Some(acc); }
+        }
+        Ok(())
+    })?;
+
+    // Fusión en orden de bloque: idéntica sin importar cuántos hilos se usaron
+    let mut acc = BlockAccumulator::new(&plan);
+    for block in blocks.into_iter().flatten() { acc.merge(block); }
+
+    let BlockAccumulator {
+        sum_x, sum_x2, min_val, max_val, success_counter, threshold_counters,
+        preview, columns, totals, period_states, ruin_first,
+    } = acc;
 
     // 3. Resultados
     let n = config.n_simulations as f64;
@@ -104,22 +348,98 @@ pub fn execute(config: MonteCarloConfig) -> Result<MonteCarloResponse, Error> {
     let mut cost_res = None;
     let mut count_res = None;
 
-    if let AnalysisMode::Probability { cost_per_event, population_size, .. } = config.analysis {
-        let p = success_counter as f64 / n;
-        prob_res = Some(p);
-        count_res = Some(success_counter);
-        cost_res = Some(p * cost_per_event * population_size);
+    match config.analysis {
+        AnalysisMode::Probability { cost_per_event, population_size, .. }
+        | AnalysisMode::Between { cost_per_event, population_size, .. } => {
+            let p = success_counter as f64 / n;
+            prob_res = Some(p);
+            count_res = Some(success_counter);
+            cost_res = Some(p * cost_per_event * population_size);
+        },
+        AnalysisMode::Aggregation => {},
     }
 
+    let threshold_res = if config.thresholds.is_empty() {
+        None
+    } else {
+        Some(config.thresholds.iter().zip(&threshold_counters).map(|(check, &count)| ThresholdResult {
+            threshold: check.threshold,
+            operator: check.operator,
+            count,
+            probability: count as f64 / n,
+        }).collect())
+    };
+
+    let risk_res = if config.risk_levels.is_empty() {
+        None
+    } else {
+        // Copia ordenada: `totals` debe seguir alineado con `columns` para la sensibilidad
+        let mut sorted = totals.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Some(risk_metrics(&sorted, &config.risk_levels))
+    };
+
+    let sensitivity_res = if keep_columns {
+        let names: Vec<String> = config.variables.iter().map(|v| v.name.clone()).collect();
+        Some(sensitivity::analyze(&names, &columns, &totals))
+    } else {
+        None
+    };
+
+    let multi_period_res = config.multi_period.as_ref()
+        .map(|mp| summarize_periods(mp, period_states, &ruin_first, config.n_simulations));
+
     Ok(MonteCarloResponse {
         iterations: config.n_simulations,
         mean,
         std_dev,
         min: min_val,
         max: max_val,
+        seed,
         samples_preview: preview, // Ahora enviamos la estructura correcta
         success_count: count_res,
         probability: prob_res,
         expected_cost: cost_res,
+        sensitivity: sensitivity_res,
+        threshold_results: threshold_res,
+        risk_metrics: risk_res,
+        multi_period: multi_period_res,
     })
+}
+
+/// VaR y CVaR sobre los totales ORDENADOS (cola superior = pérdida).
+fn risk_metrics(sorted_totals: &[f64], levels: &[f64]) -> Vec<RiskMetric> {
+    levels.iter().map(|&level| {
+        let var = percentile_sorted(sorted_totals, level);
+        let tail = &sorted_totals[sorted_totals.partition_point(|&x| x < var)..];
+        let cvar = if tail.is_empty() { var } else { tail.iter().sum::<f64>() / tail.len() as f64 };
+        RiskMetric { level, var, cvar }
+    }).collect()
+}
+
+/// Percentiles por periodo (fan chart) y probabilidad de ruina acumulada.
+fn summarize_periods(mp: &MultiPeriodConfig, period_states: Vec<Vec<f64>>, ruin_first: &[usize], n_sims: usize) -> MultiPeriodResult {
+    let n = n_sims as f64;
+    let periods = period_states.into_iter().enumerate().map(|(t, mut states)| {
+        let mean = states.iter().sum::<f64>() / n;
+        states.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        PeriodStats {
+            period: t + 1,
+            mean,
+            percentiles: mp.percentiles.iter().map(|&p| percentile_sorted(&states, p)).collect(),
+        }
+    }).collect();
+
+    let cumulative_ruin: Option<Vec<f64>> = mp.ruin.as_ref().map(|_| {
+        ruin_first.iter()
+            .scan(0usize, |cum, &c| { *cum += c; Some(*cum as f64 / n) })
+            .collect()
+    });
+
+    MultiPeriodResult {
+        percentile_levels: mp.percentiles.clone(),
+        periods,
+        ruin_probability: cumulative_ruin.as_ref().and_then(|c| c.last().copied()),
+        cumulative_ruin,
+    }
 }
\ No newline at end of file
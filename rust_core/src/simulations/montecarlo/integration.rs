@@ -0,0 +1,353 @@
+// src/simulations/montecarlo/integration.rs
+// Integración Monte Carlo: acierto-fallo (hit-or-miss) y media muestral sobre un hiperrectángulo,
+// con fuente pseudo o cuasi-aleatoria. Incluye la demo clásica de estimación de pi.
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_char;
+use crate::errors::Error;
+use crate::expression::Expression;
+use crate::json_helpers::run_json;
+use crate::sampling::quasi::{QuasiSequence, QuasiSpec};
+
+// Puntos de la corrida piloto que estima el rango de f para acierto-fallo
+const PILOT_POINTS: usize = 1000;
+
+fn default_convergence_points() -> usize {
+    100
+}
+
+fn default_points_returned() -> usize {
+    2000
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationMethod {
+    HitOrMiss,
+    SampleMean,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum IntegrationMode {
+    // ∫ f sobre [a1, b1] x ... x [ad, bd]
+    Function {
+        expression: String,
+        bounds: Vec<(f64, f64)>,
+        method: IntegrationMethod,
+        // Acierto-fallo: caja vertical [y_min, y_max] que contiene a f; si falta se estima
+        #[serde(default)]
+        y_range: Option<(f64, f64)>,
+        #[serde(default)]
+        exact: Option<f64>, // Valor exacto conocido, para reportar el error
+    },
+    // Puntos en el cuadrado unitario: pi ≈ 4 · (dentro del cuarto de círculo) / n
+    Pi,
+}
+
+#[derive(Deserialize)]
+pub struct IntegrationRequest {
+    #[serde(flatten)]
+    pub mode: IntegrationMode,
+    pub n_samples: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub quasi: Option<QuasiSpec>,
+    #[serde(default = "default_convergence_points")]
+    pub convergence_points: usize,
+    #[serde(default = "default_points_returned")]
+    pub points_returned: usize, // Nube de puntos (x, y, dentro) para graficar
+}
+
+#[derive(Serialize)]
+pub struct ConvergencePoint {
+    pub n: usize,
+    pub estimate: f64,
+    pub std_error: f64,
+}
+
+#[derive(Serialize)]
+pub struct CloudPoint {
+    pub x: f64,
+    pub y: f64,
+    pub inside: bool, // Acierto (bajo la curva / dentro del círculo)
+}
+
+#[derive(Serialize)]
+pub struct IntegrationResponse {
+    pub estimate: f64,
+    pub std_error: f64, // Con fuente cuasi-aleatoria es solo orientativo
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub n_samples: usize,
+    pub seed: u64,
+    pub volume: f64,
+    pub y_range: Option<(f64, f64)>,
+    pub range_violations: usize, // Puntos con f fuera de y_range (sesgan el acierto-fallo)
+    pub exact: Option<f64>,
+    pub absolute_error: Option<f64>,
+    pub convergence: Vec<ConvergencePoint>,
+    pub points: Vec<CloudPoint>,
+}
+
+/// Fuente de puntos en [0,1)^d: ChaCha20 o una secuencia de baja discrepancia.
+enum UnitSource {
+    Pseudo(Box<ChaCha20Rng>),
+    Quasi(QuasiSequence, u64),
+}
+
+impl UnitSource {
+    fn next(&mut self, out: &mut [f64]) {
+        match self {
+            UnitSource::Pseudo(rng) => {
+                for x in out.iter_mut() { *x = rng.gen(); }
+            },
+            UnitSource::Quasi(seq, index) => {
+                *index += 1;
+                seq.point(*index, out);
+            },
+        }
+    }
+}
+
+/// Estimador en línea: estimate = offset + scale · media(h), con h el valor por muestra.
+struct RunningEstimate {
+    offset: f64,
+    scale: f64,
+    sum: f64,
+    sum2: f64,
+    n: usize,
+}
+
+impl RunningEstimate {
+    fn push(&mut self, h: f64) {
+        self.sum += h;
+        self.sum2 += h * h;
+        self.n += 1;
+    }
+
+    fn estimate(&self) -> f64 {
+        self.offset + self.scale * self.sum / self.n as f64
+    }
+
+    fn std_error(&self) -> f64 {
+        let n = self.n as f64;
+        if n < 2.0 { return f64::NAN; }
+        let mean = self.sum / n;
+        let var = ((self.sum2 - n * mean * mean) / (n - 1.0)).max(0.0);
+        self.scale.abs() * (var / n).sqrt()
+    }
+}
+
+pub fn integrate(req: &IntegrationRequest) -> Result<IntegrationResponse, Error> {
+    if req.n_samples < 2 { return Err(Error::Other("n_samples debe ser >= 2".into())); }
+    let seed = req.seed.unwrap_or_else(|| rand::thread_rng().gen());
+
+    // Dimensiones de la fuente uniforme y función a evaluar por punto
+    let (expression, bounds, method, exact) = match &req.mode {
+        IntegrationMode::Function { expression, bounds, method, exact, .. } => {
+            let expr = Expression::parse(expression)?;
+            if bounds.is_empty() { return Err(Error::Other("Se requiere al menos un intervalo de integración".into())); }
+            if expr.n_vars() > bounds.len() {
+                return Err(Error::Other(format!(
+                    "La expresión usa {} variables y solo hay {} intervalos", expr.n_vars(), bounds.len()
+                )));
+            }
+            if let Some((a, b)) = bounds.iter().find(|(a, b)| !(a < b && a.is_finite() && b.is_finite())) {
+                return Err(Error::Other(format!("Intervalo inválido [{}, {}]", a, b)));
+            }
+            (Some(expr), bounds.clone(), *method, *exact)
+        },
+        IntegrationMode::Pi => (None, vec![(0.0, 1.0), (0.0, 1.0)], IntegrationMethod::HitOrMiss, Some(std::f64::consts::PI)),
+    };
+    let d = bounds.len();
+    let volume: f64 = bounds.iter().map(|(a, b)| b - a).product();
+    let f = |x: &[f64]| expression.as_ref().map_or(0.0, |e| e.eval(x));
+
+    // Acierto-fallo con función: una dimensión extra para la altura
+    let hit_or_miss_fn = expression.is_some() && method == IntegrationMethod::HitOrMiss;
+    let src_dims = if hit_or_miss_fn { d + 1 } else { d };
+    let mut source = match &req.quasi {
+        Some(spec) => UnitSource::Quasi(QuasiSequence::new(spec, src_dims, seed)?, 0),
+        None => UnitSource::Pseudo(Box::new(ChaCha20Rng::seed_from_u64(seed))),
+    };
+    let mut u = vec![0.0; src_dims];
+    let mut x = vec![0.0; d];
+    let to_box = |u: &[f64], x: &mut [f64]| {
+        for ((xi, ui), (a, b)) in x.iter_mut().zip(u).zip(&bounds) { *xi = a + (b - a) * ui; }
+    };
+
+    // Rango vertical para acierto-fallo: dado por el usuario o por una corrida piloto (+10%)
+    let y_range = match &req.mode {
+        IntegrationMode::Function { y_range: Some((lo, hi)), .. } if hit_or_miss_fn => {
+            if lo >= hi { return Err(Error::Other("y_range inválido: y_min >= y_max".into())); }
+            Some((*lo, *hi))
+        },
+        _ if hit_or_miss_fn => {
+            let mut pilot = ChaCha20Rng::seed_from_u64(seed);
+            pilot.set_stream(1);
+            let (mut lo, mut hi) = (0.0f64, 0.0f64);
+            for _ in 0..PILOT_POINTS {
+                for ui in u.iter_mut() { *ui = pilot.gen(); }
+                to_box(&u, &mut x);
+                let fx = f(&x);
+                if fx.is_finite() {
+                    lo = lo.min(fx);
+                    hi = hi.max(fx);
+                }
+            }
+            let margin = 0.1 * (hi - lo).max(1e-12);
+            Some((if lo < 0.0 { lo - margin } else { 0.0 }, if hi > 0.0 { hi + margin } else { 0.0 }))
+        },
+        _ => None,
+    };
+
+    // ∫f = V · (y_min + (y_max - y_min) · P(acierto)) en acierto-fallo; V · media(f) en media muestral
+    let (offset, scale) = match (&req.mode, y_range) {
+        (IntegrationMode::Pi, _) => (0.0, 4.0),
+        (_, Some((lo, hi))) => (volume * lo, volume * (hi - lo)),
+        _ => (0.0, volume),
+    };
+    let mut running = RunningEstimate { offset, scale, sum: 0.0, sum2: 0.0, n: 0 };
+    let every = (req.n_samples / req.convergence_points.max(1)).max(1);
+    let mut convergence = Vec::new();
+    let mut points = Vec::new();
+    let mut range_violations = 0usize;
+
+    for i in 0..req.n_samples {
+        source.next(&mut u);
+        let h = match (&req.mode, y_range) {
+            (IntegrationMode::Pi, _) => {
+                let inside = u[0] * u[0] + u[1] * u[1] <= 1.0;
+                if points.len() < req.points_returned {
+                    points.push(CloudPoint { x: u[0], y: u[1], inside });
+                }
+                if inside { 1.0 } else { 0.0 }
+            },
+            (_, Some((lo, hi))) => {
+                to_box(&u[..d], &mut x);
+                let fx = f(&x);
+                if !(fx >= lo && fx <= hi) { range_violations += 1; }
+                let y = lo + (hi - lo) * u[d];
+                let inside = y <= fx;
+                if d == 1 && points.len() < req.points_returned {
+                    points.push(CloudPoint { x: x[0], y, inside });
+                }
+                if inside { 1.0 } else { 0.0 }
+            },
+            _ => {
+                to_box(&u, &mut x);
+                let fx = f(&x);
+                if !fx.is_finite() {
+                    return Err(Error::Other(format!("f no es finita en {:?}", x)));
+                }
+                fx
+            },
+        };
+        running.push(h);
+        if (i + 1) % every == 0 || i + 1 == req.n_samples {
+            convergence.push(ConvergencePoint { n: i + 1, estimate: running.estimate(), std_error: running.std_error() });
+        }
+    }
+
+    let estimate = running.estimate();
+    let std_error = running.std_error();
+    Ok(IntegrationResponse {
+        estimate,
+        std_error,
+        ci_low: estimate - 1.96 * std_error,
+        ci_high: estimate + 1.96 * std_error,
+        n_samples: req.n_samples,
+        seed,
+        volume,
+        y_range,
+        range_violations,
+        exact,
+        absolute_error: exact.map(|v| (estimate - v).abs()),
+        convergence,
+        points,
+    })
+}
+
+// Wrapper FFI (JSON -> JSON)
+pub fn integrate_json(json_request: *const c_char) -> *mut c_char {
+    run_json(json_request, integrate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(json: &str) -> IntegrationResponse {
+        integrate(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    // El estimador debe caer a menos de 4 errores estándar del valor exacto
+    fn assert_near_exact(r: &IntegrationResponse) {
+        let exact = r.exact.unwrap();
+        assert!(r.absolute_error.unwrap() < 4.0 * r.std_error, "{} vs {} (ee {})", r.estimate, exact, r.std_error);
+    }
+
+    #[test]
+    fn integrates_x_squared_with_both_methods() {
+        for method in ["hit_or_miss", "sample_mean"] {
+            let r = run(&format!(
+                r#"{{"mode": "function", "expression": "x^2", "bounds": [[0, 1]], "method": "{}",
+                    "exact": 0.3333333333333333, "n_samples": 20000, "seed": 7}}"#,
+                method
+            ));
+            assert_near_exact(&r);
+            assert!(r.absolute_error.unwrap() < 0.02);
+            assert_eq!(r.range_violations, 0);
+            assert_eq!(r.convergence.last().unwrap().n, 20000);
+        }
+        // Media muestral: Var(U²) = 4/45, ee = sqrt(4/45 / n)
+        let r = run(r#"{"mode": "function", "expression": "x^2", "bounds": [[0, 1]], "method": "sample_mean",
+                        "n_samples": 20000, "seed": 7}"#);
+        assert!((r.std_error - (4.0 / 45.0 / 20000.0f64).sqrt()).abs() < 1e-4);
+
+        // Con Sobol el error es mucho menor que el de Monte Carlo simple
+        let r = run(r#"{"mode": "function", "expression": "x^2", "bounds": [[0, 1]], "method": "sample_mean",
+                        "exact": 0.3333333333333333, "n_samples": 4096, "seed": 7, "quasi": {"sequence": "sobol"}}"#);
+        assert!(r.absolute_error.unwrap() < 1e-3);
+    }
+
+    #[test]
+    fn hit_or_miss_handles_negative_integrands() {
+        // ∫₀¹ (x² - 1/2) dx = -1/6: la caja vertical debe bajar de cero
+        let r = run(r#"{"mode": "function", "expression": "x^2 - 0.5", "bounds": [[0, 1]], "method": "hit_or_miss",
+                        "exact": -0.16666666666666666, "n_samples": 20000, "seed": 3}"#);
+        let (lo, hi) = r.y_range.unwrap();
+        assert!(lo < -0.5 && hi > 0.5);
+        assert_near_exact(&r);
+        // Con la caja dada por el usuario, sobre [0, 2] x [-1, 0]: ∫₀² -x/2 dx = -1
+        let r = run(r#"{"mode": "function", "expression": "-x/2", "bounds": [[0, 2]], "method": "hit_or_miss",
+                        "y_range": [-1, 0], "exact": -1, "n_samples": 20000, "seed": 3}"#);
+        assert_eq!(r.volume, 2.0);
+        assert_near_exact(&r);
+    }
+
+    #[test]
+    fn pi_demo_is_reproducible() {
+        let json = r#"{"mode": "pi", "n_samples": 10000, "seed": 42, "points_returned": 100}"#;
+        let (a, b) = (run(json), run(json));
+        assert_eq!(a.estimate, b.estimate);
+        assert_eq!(a.seed, 42);
+        assert_eq!(a.points.len(), 100);
+        assert!(a.points.iter().all(|p| p.inside == (p.x * p.x + p.y * p.y <= 1.0)));
+        // Hit-or-miss: ee = 4 sqrt(p(1-p)/n) con p = pi/4
+        let p = std::f64::consts::FRAC_PI_4;
+        assert!((a.std_error - 4.0 * (p * (1.0 - p) / 10000.0).sqrt()).abs() < 1e-3);
+        assert_near_exact(&a);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let err = |json: &str| integrate(&serde_json::from_str(json).unwrap()).is_err();
+        assert!(err(r#"{"mode": "function", "expression": "x*y", "bounds": [[0, 1]], "method": "sample_mean", "n_samples": 10}"#));
+        assert!(err(r#"{"mode": "function", "expression": "x", "bounds": [[1, 0]], "method": "sample_mean", "n_samples": 10}"#));
+        assert!(err(r#"{"mode": "pi", "n_samples": 1}"#));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod models;
 pub mod engine;
+pub mod sensitivity;
+pub mod integration;
 
 use std::ffi::{c_char, CStr};
 use serde::Serialize;
@@ -1,10 +1,69 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::probabilities::distribution::{Dist, DistSpec};
+use crate::sampling::quasi::QuasiSpec;
 
-#[derive(Deserialize, Clone)]
+/// Distribución de una variable simulada: cualquier `Dist` paramétrica (ya validada al leer el
+/// JSON) o datos empíricos remuestreados. En JSON usa el esquema de `DistSpec` y acepta la
+/// forma antigua, externamente etiquetada: {"Normal": {"mean": 0, "variance": 1}}.
+#[derive(Clone)]
 pub enum DistType {
-    Normal { mean: f64, variance: f64 }, // Confirmando el uso de 'variance'
-    Exponential { beta: f64 }, 
-    Uniform { min: f64, max: f64 },
+    Param(Dist),
+    Empirical {
+        data: Vec<f64>,
+        method: EmpiricalMethod,
+        bandwidth: Option<f64>, // Solo Kernel; si falta se usa la regla de Silverman
+    },
+}
+
+impl TryFrom<DistSpec> for DistType {
+    type Error = String;
+
+    fn try_from(spec: DistSpec) -> Result<Self, String> {
+        match spec {
+            DistSpec::Empirical { data, method, bandwidth } => Ok(DistType::Empirical { data, method, bandwidth }),
+            spec => Dist::try_from(spec).map(DistType::Param),
+        }
+    }
+}
+
+/// {"NegativeBinomial": {...}} -> {"type": "negative_binomial", ...}: las variantes antiguas
+/// son las de `DistSpec` en CamelCase.
+fn legacy_to_spec(value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let invalid = || "Distribución inválida: se espera {\"type\": ...} o {\"Variante\": {...}}".to_string();
+    let serde_json::Value::Object(outer) = value else { return Err(invalid()) };
+    let mut entries = outer.into_iter();
+    let (Some((variant, serde_json::Value::Object(mut fields))), None) = (entries.next(), entries.next()) else {
+        return Err(invalid());
+    };
+    let mut tag = String::with_capacity(variant.len() + 4);
+    for (i, c) in variant.chars().enumerate() {
+        if c.is_uppercase() && i > 0 { tag.push('_'); }
+        tag.extend(c.to_lowercase());
+    }
+    fields.insert("type".to_string(), serde_json::Value::String(tag));
+    Ok(serde_json::Value::Object(fields))
+}
+
+impl<'de> Deserialize<'de> for DistType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        if value.get("type").is_none() {
+            value = legacy_to_spec(value).map_err(D::Error::custom)?;
+        }
+        let spec = DistSpec::deserialize(value).map_err(D::Error::custom)?;
+        DistType::try_from(spec).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmpiricalMethod {
+    #[default]
+    #[serde(alias = "Bootstrap")]
+    Bootstrap, // Remuestreo con reemplazo de los datos
+    #[serde(alias = "Kernel")]
+    Kernel,    // Bootstrap suavizado con kernel gaussiano
 }
 
 #[derive(Deserialize, Clone)]
@@ -14,16 +73,56 @@ pub struct VariableConfig {
     pub multiplier: f64, 
 }
 
+// Operador de comparación tipado (en JSON sigue siendo "<", "<=", ">", ">=")
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub enum CompareOp {
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+}
+
+impl CompareOp {
+    #[inline(always)]
+    pub fn eval(self, value: f64, threshold: f64) -> bool {
+        match self {
+            CompareOp::Lt => value < threshold,
+            CompareOp::Le => value <= threshold,
+            CompareOp::Gt => value > threshold,
+            CompareOp::Ge => value >= threshold,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(tag = "mode_type", content = "params")]
 pub enum AnalysisMode {
     Aggregation, 
     Probability { 
         threshold: f64,
-        operator: String, 
+        operator: CompareOp, 
         cost_per_event: f64,
         population_size: f64,
-    }
+    },
+    // P(low <= total <= high)
+    Between {
+        low: f64,
+        high: f64,
+        #[serde(default)]
+        cost_per_event: f64,
+        #[serde(default)]
+        population_size: f64,
+    },
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+pub struct ThresholdCheck {
+    pub threshold: f64,
+    pub operator: CompareOp,
 }
 
 #[derive(Deserialize)]
@@ -31,6 +130,43 @@ pub struct MonteCarloConfig {
     pub n_simulations: usize,
     pub variables: Vec<VariableConfig>,
     pub analysis: AnalysisMode,
+    #[serde(default)]
+    pub sensitivity: bool, // Si es true se guardan todas las muestras para el análisis de sensibilidad
+    #[serde(default)]
+    pub thresholds: Vec<ThresholdCheck>, // Umbrales adicionales evaluados en la misma corrida
+    #[serde(default)]
+    pub risk_levels: Vec<f64>, // Niveles de confianza para VaR/CVaR, ej. [0.95, 0.99]
+    #[serde(default)]
+    pub seed: Option<u64>, // Misma semilla => mismo resultado, sin importar los hilos
+    #[serde(default)]
+    pub threads: Option<usize>, // Por defecto, todos los núcleos disponibles
+    #[serde(default)]
+    pub multi_period: Option<MultiPeriodConfig>,
+    // Fuente cuasi-aleatoria (Halton/Sobol) en lugar de ChaCha20; las variables se
+    // generan por transformada inversa, una dimensión por variable y periodo
+    #[serde(default)]
+    pub quasi: Option<QuasiSpec>,
+}
+
+fn default_fan_percentiles() -> Vec<f64> {
+    vec![0.05, 0.25, 0.5, 0.75, 0.95]
+}
+
+// Modo multi-periodo: en cada periodo se re-muestrean las variables y su suma se
+// acumula sobre el estado (saldo de caja, inventario, ...). El total de la
+// iteración pasa a ser el estado final.
+#[derive(Deserialize)]
+pub struct MultiPeriodConfig {
+    pub n_periods: usize,
+    pub initial_state: f64,
+    #[serde(default)]
+    pub min_state: Option<f64>, // Ej. inventario no negativo
+    #[serde(default)]
+    pub max_state: Option<f64>, // Ej. capacidad de almacén
+    #[serde(default)]
+    pub ruin: Option<ThresholdCheck>, // Ej. saldo < 0 en cualquier periodo
+    #[serde(default = "default_fan_percentiles")]
+    pub percentiles: Vec<f64>,
 }
 
 // --- NUEVA ESTRUCTURA PARA EL DETALLE (Corrección del error) ---
@@ -47,9 +183,117 @@ pub struct MonteCarloResponse {
     pub std_dev: f64,
     pub min: f64,
     pub max: f64,
+    pub seed: u64, // Semilla usada (para reproducir la corrida)
     // CAMBIO AQUÍ: Vector de IterationDetail en lugar de f64
     pub samples_preview: Vec<IterationDetail>, 
     pub success_count: Option<usize>,
     pub probability: Option<f64>,
     pub expected_cost: Option<f64>,
-}
\ No newline at end of file
+    pub sensitivity: Option<SensitivityReport>,
+    pub threshold_results: Option<Vec<ThresholdResult>>,
+    pub risk_metrics: Option<Vec<RiskMetric>>,
+    pub multi_period: Option<MultiPeriodResult>,
+}
+
+#[derive(Serialize)]
+pub struct PeriodStats {
+    pub period: usize,
+    pub mean: f64,
+    pub percentiles: Vec<f64>, // En el mismo orden que `percentile_levels`
+}
+
+#[derive(Serialize)]
+pub struct MultiPeriodResult {
+    pub percentile_levels: Vec<f64>,
+    pub periods: Vec<PeriodStats>, // Fan chart
+    pub ruin_probability: Option<f64>,
+    pub cumulative_ruin: Option<Vec<f64>>, // P(ruina en algún periodo <= t)
+}
+
+#[derive(Serialize)]
+pub struct ThresholdResult {
+    pub threshold: f64,
+    pub operator: CompareOp,
+    pub count: usize,
+    pub probability: f64,
+}
+
+// El total se interpreta como pérdida/costo: valores altos son peores.
+#[derive(Serialize)]
+pub struct RiskMetric {
+    pub level: f64,
+    pub var: f64,  // Value-at-Risk: cuantil `level` del total
+    pub cvar: f64, // Conditional VaR: media de los totales >= VaR
+}
+
+// --- ANÁLISIS DE SENSIBILIDAD ---
+#[derive(Serialize)]
+pub struct VariableSensitivity {
+    pub name: String,
+    pub rank_correlation: f64,         // Spearman contra el total
+    pub contribution_to_variance: f64, // rho² / suma(rho²)
+}
+
+#[derive(Serialize)]
+pub struct TornadoBar {
+    pub name: String,
+    pub input_low: f64,   // P10 de la variable
+    pub input_high: f64,  // P90 de la variable
+    pub output_low: f64,  // Total con la variable en P10 y el resto en la mediana
+    pub output_high: f64, // Total con la variable en P90 y el resto en la mediana
+    pub swing: f64,
+}
+
+#[derive(Serialize)]
+pub struct SensitivityReport {
+    pub variables: Vec<VariableSensitivity>,
+    pub tornado_base: f64, // Total con todas las variables en su mediana
+    pub tornado: Vec<TornadoBar>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(json: &str) -> Dist {
+        match serde_json::from_str::<DistType>(json).unwrap() {
+            DistType::Param(dist) => dist,
+            DistType::Empirical { .. } => panic!("{json} no es paramétrica"),
+        }
+    }
+
+    fn error(json: &str) -> String {
+        serde_json::from_str::<DistType>(json).map(|_| ()).expect_err(json).to_string()
+    }
+
+    #[test]
+    fn legacy_and_tagged_forms_resolve_to_the_same_dist() {
+        let pairs = [
+            (r#"{"Normal": {"mean": 1, "variance": 4}}"#, r#"{"type": "normal", "mean": 1, "std": 2}"#),
+            (r#"{"Erlang": {"k": 3, "beta": 2}}"#, r#"{"type": "gamma", "shape": 3, "scale": 2}"#),
+            (r#"{"NegativeBinomial": {"r": 2, "p": 0.5}}"#, r#"{"type": "negative_binomial", "r": 2, "p": 0.5}"#),
+            (r#"{"StudentT": {"df": 5}}"#, r#"{"type": "t", "df": 5}"#),
+            (r#"{"Fitted": {"name": "gamma", "params": [2, 3]}}"#, r#"{"type": "gamma", "shape": 2, "scale": 3}"#),
+        ];
+        for (legacy, tagged) in pairs {
+            assert_eq!(format!("{:?}", param(legacy)), format!("{:?}", param(tagged)), "{legacy}");
+        }
+        let legacy = serde_json::from_str::<DistType>(r#"{"Empirical": {"data": [1, 2], "method": "Kernel"}}"#).unwrap();
+        assert!(matches!(legacy, DistType::Empirical { method: EmpiricalMethod::Kernel, bandwidth: None, .. }));
+    }
+
+    #[test]
+    fn invalid_parameters_fail_when_parsed_with_the_calculator_message() {
+        // Mismo mensaje que al leer un `Dist` (calculadora), en ambas formas
+        for (legacy, tagged) in [
+            (r#"{"Beta": {"alpha": -1, "beta": 2}}"#, r#"{"type": "beta", "alpha": -1, "beta": 2}"#),
+            (r#"{"Erlang": {"k": 0, "beta": 2}}"#, r#"{"type": "erlang", "k": 0, "beta": 2}"#),
+            (r#"{"Discrete": {"values": [1], "probabilities": [0.5]}}"#, r#"{"type": "discrete", "values": [1], "probabilities": [0.5]}"#),
+        ] {
+            let calculator = serde_json::from_str::<Dist>(tagged).map(|_| ()).expect_err(tagged).to_string();
+            assert_eq!(error(legacy), calculator);
+            assert_eq!(error(tagged), calculator);
+        }
+        assert!(error(r#"{"Normal": {"mean": 0, "variance": -1}}"#).contains("Varianza negativa"));
+        assert!(error(r#"{"Normal": {"mean": 0}, "Beta": {}}"#).contains("Distribución inválida"));
+    }
+}
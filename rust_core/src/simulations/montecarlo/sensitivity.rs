@@ -0,0 +1,99 @@
+use crate::aggregation::boxplot::percentile_sorted;
+use super::models::{SensitivityReport, VariableSensitivity, TornadoBar};
+
+/// Rangos (1..=n) con empates promediados, necesarios para Spearman.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let n = values.len();
+    let mut idx: Vec<usize> = (0..n).collect();
+    idx.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut out = vec![0.0; n];
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && values[idx[j + 1]] == values[idx[i]] { j += 1; }
+        // Promedio de las posiciones i..=j (base 1)
+        let avg = (i + j) as f64 / 2.0 + 1.0;
+        for &k in &idx[i..=j] { out[k] = avg; }
+        i = j + 1;
+    }
+    out
+}
+
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    if n < 2.0 { return 0.0; }
+    let mx = x.iter().sum::<f64>() / n;
+    let my = y.iter().sum::<f64>() / n;
+    let mut sxy = 0.0;
+    let mut sxx = 0.0;
+    let mut syy = 0.0;
+    for (a, b) in x.iter().zip(y) {
+        let dx = a - mx;
+        let dy = b - my;
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 || syy == 0.0 { 0.0 } else { sxy / (sxx * syy).sqrt() }
+}
+
+/// Correlación de rangos de Spearman.
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    pearson(&ranks(x), &ranks(y))
+}
+
+/// Análisis de sensibilidad sobre las muestras completas de la corrida.
+/// `columns[i]` contiene todas las muestras de la variable i (ya multiplicadas)
+/// y `totals` el total de cada iteración.
+pub fn analyze(names: &[String], columns: &[Vec<f64>], totals: &[f64]) -> SensitivityReport {
+    // 1. Spearman y contribución a la varianza (rho² normalizado, estilo Crystal Ball)
+    let rhos: Vec<f64> = columns.iter().map(|c| spearman(c, totals)).collect();
+    let sum_sq: f64 = rhos.iter().map(|r| r * r).sum();
+
+    let variables = names.iter().zip(&rhos).map(|(name, &rho)| VariableSensitivity {
+        name: name.clone(),
+        rank_correlation: rho,
+        contribution_to_variance: if sum_sq > 0.0 { rho * rho / sum_sq } else { 0.0 },
+    }).collect();
+
+    // 2. Tornado: P10/P90 de cada entrada con el resto en su mediana
+    let mut p10 = Vec::with_capacity(columns.len());
+    let mut p50 = Vec::with_capacity(columns.len());
+    let mut p90 = Vec::with_capacity(columns.len());
+    for c in columns {
+        let mut sorted = c.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        p10.push(percentile_sorted(&sorted, 0.10));
+        p50.push(percentile_sorted(&sorted, 0.50));
+        p90.push(percentile_sorted(&sorted, 0.90));
+    }
+
+    let tornado_base = model_total(&p50);
+    let mut tornado: Vec<TornadoBar> = names.iter().enumerate().map(|(i, name)| {
+        let mut inputs = p50.clone();
+        inputs[i] = p10[i];
+        let output_low = model_total(&inputs);
+        inputs[i] = p90[i];
+        let output_high = model_total(&inputs);
+        TornadoBar {
+            name: name.clone(),
+            input_low: p10[i],
+            input_high: p90[i],
+            output_low,
+            output_high,
+            swing: (output_high - output_low).abs(),
+        }
+    }).collect();
+
+    // Barras ordenadas de mayor a menor impacto
+    tornado.sort_by(|a, b| b.swing.partial_cmp(&a.swing).unwrap_or(std::cmp::Ordering::Equal));
+
+    SensitivityReport { variables, tornado_base, tornado }
+}
+
+/// Modelo de salida: el total de una iteración es la suma de las variables.
+#[inline(always)]
+fn model_total(values: &[f64]) -> f64 {
+    values.iter().sum()
+}
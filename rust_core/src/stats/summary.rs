@@ -68,12 +68,12 @@ pub fn calculate_summary_sorted(sorted_data: &[f64], nbins: usize) -> SummarySta
 
     let cv = if mean.abs() < 1e-9 {0.0} else {(std_sample/mean) * 100.0};
 
-    let skewness = if m2 == 0.0 { 0.0 } else { ((n as f64) * m3) / m2.powf(1.5) };
+    let skewness = if m2 == 0.0 { 0.0 } else { (n as f64).sqrt() * m3 / m2.powf(1.5) };
     let kurtosis_excess = if m2 == 0.0 { -3.0 } else { ((n as f64) * m4) / (m2 * m2) - 3.0 };
 
     // Mediana (Acceso directo O(1) porque ya está ordenado)
     let mid = n / 2;
-    let median = if n % 2 == 0 {
+    let median = if n.is_multiple_of(2) {
         (sorted_data[mid - 1] + sorted_data[mid]) * 0.5
     } else {
         sorted_data[mid]
@@ -137,4 +137,23 @@ pub fn summary_stats_json(ptr: *const f64, len: usize) -> Result<*mut std::os::r
         let stats = calculate_summary_sorted(&data, k);
         Ok(to_cstring(&stats))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moments_match_hand_computed_sample() {
+        // Desvíos -3, -2, -1, 0, 6: m2 = 50, m3 = 180, m4 = 1394
+        let s = calculate_summary_sorted(&[1.0, 2.0, 3.0, 4.0, 10.0], 3);
+        assert_eq!(s.mean, 4.0);
+        assert_eq!(s.variance_pop, 10.0);
+        // g1 = √n · m3 / m2^1.5, g2 = n · m4 / m2² - 3
+        assert!((s.skewness - 1.138_419_957_660_616_4).abs() < 1e-12, "{}", s.skewness);
+        assert!((s.kurtosis_excess + 0.212).abs() < 1e-12, "{}", s.kurtosis_excess);
+
+        let symmetric = calculate_summary_sorted(&[1.0, 2.0, 3.0], 2);
+        assert_eq!(symmetric.skewness, 0.0);
+    }
+}
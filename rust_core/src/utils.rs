@@ -12,7 +12,7 @@ pub fn sturges_bins(n: usize) -> usize {
     // Usamos round() para el redondeo estándar, no ceil()
     let k = k_f64 as usize;
 
-    if k % 2 == 0 { k + 1 } else { k }
+    if k.is_multiple_of(2) { k + 1 } else { k }
 }
 
 /// Safe clamp index calculation